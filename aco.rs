@@ -0,0 +1,229 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use std::time::Instant;
+use crate::derive_seed;
+use crate::checkpoint::Snapshot;
+use crate::mttsp::{distance, interception_point, Instance, Solution};
+use crate::solution::RunStats;
+use crate::solver::{Budget, Params, Solver};
+
+// Generations between snapshots of the colony
+const ACO_SNAPSHOT_INTERVAL: usize = 10;
+
+fn select_random_index<R: Rng>(v: &[(usize, f64)], rng: &mut R) -> usize {
+    let mut rand_value = rng.gen_range(0.0..=1.0);
+    for &(i, value) in v.iter() {
+        if rand_value < value {
+            return i;
+        }
+        rand_value -= value;
+    }
+    0
+}
+
+struct Ant {
+    trail: Vec<usize>,
+    eval: i64,
+}
+
+impl Ant {
+    fn new() -> Self {
+        Self {
+            trail: Vec::new(),
+            eval: 0,
+        }
+    }
+}
+
+struct Colony {
+    pheromones: Vec<Vec<Vec<f64>>>,
+    ants: Vec<Ant>,
+}
+
+impl Colony {
+    fn new(n: usize, n_ants: usize) -> Self {
+        let mut ants: Vec<Ant> = vec![];
+        for _ in 0..n_ants {
+            ants.push(Ant::new());
+        }
+        Self {
+            pheromones: vec![vec![vec![1.5; n]; n]; n-1],
+            ants,
+        }
+    }
+
+    fn create_trails(&mut self, instance: &Instance, alfa: f64, beta: f64, q0: f64, seed: u64) {
+        self.ants.par_iter_mut().enumerate().for_each(|(a, ant)| {
+            let mut visited = vec![false; self.pheromones.len() + 1];
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, a as u64));
+            let mut which: usize = 0;
+            let mut current_time = 0.0;
+            let mut dist: f64;
+            ant.trail.clear();
+            visited[which] = true;
+            ant.trail.push(which);
+    
+            for i in 0..self.pheromones.len() {
+                let mut v: Vec<_> = self.pheromones[i][which].iter().enumerate()
+                    .filter(|&(i, _)| !visited[i])
+                    .map(|(i, &value)| (i, value))
+                    .collect();
+                let mut sum_prob = 0.0;
+                if v.is_empty() {break;}
+                for option in v.iter_mut() {
+                    let inter_point = interception_point(current_time, &instance.targets[option.0],
+                        instance.targets[which].x + current_time*instance.targets[which].x_speed, instance.targets[which].y + current_time*instance.targets[which].y_speed, instance.agent_speed);
+                    let distance_travelled = distance(instance.targets[which].x + current_time*instance.targets[which].x_speed,
+                        instance.targets[which].y + current_time*instance.targets[which].y_speed, inter_point.0, inter_point.1); 
+                    option.1 = option.1.powf(alfa) * (1.0/distance_travelled).powf(beta);
+                    sum_prob += option.1;
+                }
+                for option in v.iter_mut() {
+                    option.1 /= sum_prob;
+                }
+                let prev = (instance.targets[which].x + current_time*instance.targets[which].x_speed,
+                    instance.targets[which].y + current_time*instance.targets[which].y_speed);
+
+                let q = rng.gen_range(0.0..1.0);
+                if q < q0 {
+                    which = select_random_index(&v, &mut rng);
+                } else {
+                    which = v[rng.gen_range(0..v.len())].0;
+                }
+
+                let inter_point2 = interception_point(current_time, &instance.targets[which],
+                    prev.0, prev.1, instance.agent_speed);
+                dist = distance(prev.0, prev.1, inter_point2.0,
+                    inter_point2.1);
+                current_time += dist / instance.agent_speed;
+                visited[which] = true;
+                ant.trail.push(which);
+            }
+        });
+    }
+    
+    fn reinforcement(&mut self, instance: &Instance) {
+        for ant in &mut self.ants {
+            ant.eval = instance.evaluate(&ant.trail);
+            for i in 0..(ant.trail.len()-1) {
+                self.pheromones[i][ant.trail[i]][ant.trail[i+1]] += 1.0 / (ant.eval as f64);
+            }
+        }
+    }
+
+    fn snapshot(&self, generation: usize, best_trail: &[usize]) -> Snapshot {
+        Snapshot {
+            iteration: generation,
+            tours: vec![best_trail.to_vec()],
+            values: self.pheromones.iter().flatten().flatten().cloned().collect(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        let mut values = snapshot.values.iter();
+        for step in self.pheromones.iter_mut() {
+            for row in step.iter_mut() {
+                for value in row.iter_mut() {
+                    *value = *values.next().expect("Snapshot da colonia incompleto");
+                }
+            }
+        }
+    }
+
+    fn evaporation(&mut self, evaporation_factor: f64) {
+        self.pheromones.par_iter_mut().for_each(|v| {
+            for row in v.iter_mut() {
+                for value in row.iter_mut() {
+                    *value = *value*(1.0 - evaporation_factor) + f64::EPSILON;
+                }
+            }
+        })
+    }
+}
+
+impl Instance {
+    pub fn aco(&self, params: &AcoParams, budget: &Budget, seed: u64) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let max_gen = budget.iterations_or(params.max_gen as u64) as usize;
+        let snapshot = params.snapshot.as_deref();
+        let mut colony = Colony::new(self.targets.len(), params.n_ants);
+        // The targets in file order until a generation finds better, so that a budget that
+        // allows no generation still returns a tour
+        let mut best_trail: Vec<usize> = (0..self.targets.len()).collect();
+        let mut eval_best = self.evaluate(&best_trail);
+        let mut first_gen = 0;
+        if let Some(saved) = snapshot.and_then(Snapshot::load) {
+            first_gen = saved.iteration;
+            colony.restore(&saved);
+            if !saved.tours[0].is_empty() {
+                best_trail = saved.tours[0].clone();
+                eval_best = self.evaluate(&best_trail);
+            }
+        }
+        for i in first_gen..max_gen {
+            if budget.expired(start) {
+                break;
+            }
+            if let Some(path) = snapshot {
+                if i > first_gen && i % ACO_SNAPSHOT_INTERVAL == 0 {
+                    colony.snapshot(i, &best_trail).save(path);
+                }
+            }
+            stats.iterations += 1;
+            stats.evaluations += colony.ants.len() as u64;
+            colony.create_trails(self, params.alfa, params.beta, params.q0, derive_seed(seed, i as u64));
+            colony.evaporation(params.evaporation_factor);
+            colony.reinforcement(self);
+            for ant in &colony.ants { 
+                if ant.eval < eval_best {
+                    best_trail = ant.trail.clone();
+                    eval_best = ant.eval;
+                }
+            }
+        }
+        if let Some(path) = snapshot {
+            Snapshot::remove(path);
+        }
+        stats.time = start.elapsed();
+        Solution { tour: best_trail, eval: eval_best, stats }
+    }
+}
+
+pub struct AcoParams {
+    pub n_ants: usize,
+    pub max_gen: usize,
+    pub alfa: f64,
+    pub beta: f64,
+    pub q0: f64,
+    pub evaporation_factor: f64,
+    // File where the colony is saved every ACO_SNAPSHOT_INTERVAL generations
+    pub snapshot: Option<String>,
+}
+
+impl AcoParams {
+    pub const NAMES: [&'static str; 7] = ["n_ants", "max_gen", "alfa", "beta", "q0", "evaporation_factor", "snapshot"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            n_ants: params.get_usize("n_ants", 3000),
+            max_gen: params.get_usize("max_gen", 200),
+            alfa: params.get("alfa", 1.0),
+            beta: params.get("beta", 5.0),
+            q0: params.get("q0", 0.96),
+            evaporation_factor: params.get("evaporation_factor", 0.1),
+            snapshot: params.get_string("snapshot"),
+        }
+    }
+}
+
+pub struct Aco {
+    pub params: AcoParams,
+}
+
+impl Solver<Instance, Solution> for Aco {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        instance.aco(&self.params, budget, rng.gen())
+    }
+}
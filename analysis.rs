@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct RunRecord {
+    pub instance: String,
+    pub solver: String,
+    pub run: usize,
+    pub eval: i64,
+    pub time: Duration,
+}
+
+#[derive(Debug)]
+pub struct Summary {
+    pub instance: String,
+    pub solver: String,
+    pub runs: usize,
+    pub best: i64,
+    pub mean: f64,
+    pub median: f64,
+    pub std: f64,
+    pub mean_gap: Option<f64>,
+    pub mean_time: Duration,
+}
+
+#[derive(Debug)]
+pub struct WilcoxonResult {
+    pub n: usize,
+    pub w_plus: f64,
+    pub w_minus: f64,
+    pub z: f64,
+    pub p_value: f64,
+}
+
+#[derive(Debug)]
+pub struct FriedmanResult {
+    pub solvers: Vec<String>,
+    pub blocks: usize,
+    pub mean_ranks: Vec<f64>,
+    pub statistic: f64,
+    pub p_value: f64,
+    pub critical_difference: f64,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid-1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    let sum_sq: f64 = values.iter().map(|v| (v - m) * (v - m)).sum();
+    (sum_sq / (values.len() - 1) as f64).sqrt()
+}
+
+// Ranks starting at 1, ties receive the average of the ranks they span
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j+1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for k in i..=j {
+            ranks[order[k]] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+fn normal_two_sided_p(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0)
+}
+
+// Inverse of the standard normal CDF (Acklam's rational approximation)
+fn normal_quantile(p: f64) -> f64 {
+    let a = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+        1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    let b = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+        6.680131188771972e+01, -1.328068155288572e+01];
+    let c = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+        -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    let d = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+        3.754408661907416e+00];
+    let low = 0.02425;
+    if p < low {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((c[0]*q + c[1])*q + c[2])*q + c[3])*q + c[4])*q + c[5])
+            / ((((d[0]*q + d[1])*q + d[2])*q + d[3])*q + 1.0)
+    } else if p <= 1.0 - low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((a[0]*r + a[1])*r + a[2])*r + a[3])*r + a[4])*r + a[5])*q
+            / (((((b[0]*r + b[1])*r + b[2])*r + b[3])*r + b[4])*r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+// Student's t quantile by the Cornish-Fisher expansion around the normal quantile
+fn t_quantile(p: f64, degrees_of_freedom: f64) -> f64 {
+    let z = normal_quantile(p);
+    let z3 = z * z * z;
+    let z5 = z3 * z * z;
+    z + (z3 + z) / (4.0 * degrees_of_freedom)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * z) / (96.0 * degrees_of_freedom * degrees_of_freedom)
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    let coefficients = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let mut series = 1.000000000190015;
+    for c in coefficients {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+// Upper regularized incomplete gamma Q(a, x)
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    if x < a + 1.0 {
+        let mut ap = a;
+        let mut sum = 1.0 / a;
+        let mut del = sum;
+        for _ in 0..500 {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * 1e-14 {
+                break;
+            }
+        }
+        1.0 - sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    } else {
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny { d = tiny; }
+            c = b + an / c;
+            if c.abs() < tiny { c = tiny; }
+            d = 1.0 / d;
+            let del = d * c;
+            h *= del;
+            if (del - 1.0).abs() < 1e-14 {
+                break;
+            }
+        }
+        (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    }
+}
+
+fn chi_square_upper_p(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    gamma_q(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+}
+
+// Studentized range statistic divided by sqrt(2), alpha = 0.05 (Demsar, 2006)
+fn nemenyi_q(k: usize) -> f64 {
+    let table = [0.0, 0.0, 1.960, 2.343, 2.569, 2.728, 2.850, 2.949, 3.031, 3.102, 3.164];
+    if k < table.len() { table[k] } else { table[table.len()-1] }
+}
+
+pub fn summarize(records: &[RunRecord], best_known: &HashMap<String, i64>) -> Vec<Summary> {
+    let mut keys: Vec<(String, String)> = vec![];
+    for r in records {
+        let key = (r.instance.clone(), r.solver.clone());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let mut summaries = vec![];
+    for (instance, solver) in keys {
+        let group: Vec<&RunRecord> = records.iter()
+            .filter(|r| r.instance == instance && r.solver == solver)
+            .collect();
+        let evals: Vec<f64> = group.iter().map(|r| r.eval as f64).collect();
+        let total_time: Duration = group.iter().map(|r| r.time).sum();
+        // A relative gap to a best known value of 0 is undefined
+        let mean_gap = best_known.get(&instance).filter(|&&bk| bk != 0).map(|&bk| {
+            let gaps: Vec<f64> = evals.iter().map(|e| 100.0 * (e - bk as f64) / bk as f64).collect();
+            mean(&gaps)
+        });
+
+        summaries.push(Summary {
+            runs: group.len(),
+            best: group.iter().map(|r| r.eval).min().unwrap(),
+            mean: mean(&evals),
+            median: median(&evals),
+            std: std_dev(&evals),
+            mean_gap,
+            mean_time: total_time / group.len() as u32,
+            instance,
+            solver,
+        });
+    }
+    summaries
+}
+
+pub fn wilcoxon_signed_rank(a: &[f64], b: &[f64]) -> WilcoxonResult {
+    let differences: Vec<f64> = a.iter().zip(b.iter())
+        .map(|(x, y)| x - y)
+        .filter(|d| *d != 0.0)
+        .collect();
+    let n = differences.len();
+    if n == 0 {
+        return WilcoxonResult { n, w_plus: 0.0, w_minus: 0.0, z: 0.0, p_value: 1.0 };
+    }
+
+    let absolute: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+    let ranks = average_ranks(&absolute);
+    let mut w_plus = 0.0;
+    let mut w_minus = 0.0;
+    for i in 0..n {
+        if differences[i] > 0.0 {
+            w_plus += ranks[i];
+        } else {
+            w_minus += ranks[i];
+        }
+    }
+
+    let nf = n as f64;
+    let expected = nf * (nf + 1.0) / 4.0;
+    let mut variance = nf * (nf + 1.0) * (2.0 * nf + 1.0) / 24.0;
+    let mut sorted = absolute.clone();
+    sorted.sort_by(|x, y| x.total_cmp(y));
+    let mut i = 0;
+    let mut has_ties = false;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && sorted[j+1] == sorted[i] {
+            j += 1;
+        }
+        let t = (j - i + 1) as f64;
+        if t > 1.0 {
+            has_ties = true;
+            variance -= (t * t * t - t) / 48.0;
+        }
+        i = j + 1;
+    }
+    let w = w_plus.min(w_minus);
+    let z = if variance > 0.0 {
+        let correction = if w < expected { 0.5 } else { 0.0 };
+        (w - expected + correction) / variance.sqrt()
+    } else {
+        0.0
+    };
+
+    let p_value = if !has_ties && n <= 30 {
+        // Exact null distribution of W+ by counting subsets of {1..n} per rank sum
+        let max_sum = n * (n + 1) / 2;
+        let mut counts = vec![0.0f64; max_sum + 1];
+        counts[0] = 1.0;
+        for rank in 1..=n {
+            for s in (rank..=max_sum).rev() {
+                counts[s] += counts[s - rank];
+            }
+        }
+        let total = 2f64.powi(n as i32);
+        let tail: f64 = counts[..=(w as usize)].iter().sum();
+        (2.0 * tail / total).min(1.0)
+    } else {
+        normal_two_sided_p(z)
+    };
+
+    WilcoxonResult { n, w_plus, w_minus, z, p_value }
+}
+
+// Pairs the runs of two solvers by (instance, run)
+pub fn paired_evals(records: &[RunRecord], solver_a: &str, solver_b: &str) -> (Vec<f64>, Vec<f64>) {
+    let mut a = vec![];
+    let mut b = vec![];
+    for ra in records.iter().filter(|r| r.solver == solver_a) {
+        if let Some(rb) = records.iter()
+            .find(|r| r.solver == solver_b && r.instance == ra.instance && r.run == ra.run) {
+            a.push(ra.eval as f64);
+            b.push(rb.eval as f64);
+        }
+    }
+    (a, b)
+}
+
+pub fn solvers(records: &[RunRecord]) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for r in records {
+        if !names.contains(&r.solver) {
+            names.push(r.solver.clone());
+        }
+    }
+    names
+}
+
+pub fn pairwise_wilcoxon(records: &[RunRecord]) -> Vec<(String, String, WilcoxonResult)> {
+    let names = solvers(records);
+    let mut results = vec![];
+    for i in 0..names.len() {
+        for j in i+1..names.len() {
+            let (a, b) = paired_evals(records, &names[i], &names[j]);
+            results.push((names[i].clone(), names[j].clone(), wilcoxon_signed_rank(&a, &b)));
+        }
+    }
+    results
+}
+
+// Each row of `blocks` holds the values of every solver on the same block (lower is better)
+pub fn friedman(solvers: &[String], blocks: &[Vec<f64>]) -> FriedmanResult {
+    let k = solvers.len();
+    let n = blocks.len();
+    // Without complete blocks there is nothing to rank
+    if n == 0 {
+        return FriedmanResult {
+            solvers: solvers.to_vec(),
+            blocks: 0,
+            mean_ranks: vec![0.0; k],
+            statistic: 0.0,
+            p_value: 1.0,
+            critical_difference: f64::INFINITY,
+        };
+    }
+    let mut rank_sums = vec![0.0; k];
+    let mut tie_correction = 0.0;
+    for block in blocks {
+        let ranks = average_ranks(block);
+        for s in 0..k {
+            rank_sums[s] += ranks[s];
+        }
+        let mut sorted = block.clone();
+        sorted.sort_by(|x, y| x.total_cmp(y));
+        let mut i = 0;
+        while i < k {
+            let mut j = i;
+            while j + 1 < k && sorted[j+1] == sorted[i] {
+                j += 1;
+            }
+            let t = (j - i + 1) as f64;
+            tie_correction += t * t * t - t;
+            i = j + 1;
+        }
+    }
+
+    let kf = k as f64;
+    let nf = n as f64;
+    let mean_ranks: Vec<f64> = rank_sums.iter().map(|r| r / nf).collect();
+    let mut statistic = 12.0 / (nf * kf * (kf + 1.0)) * rank_sums.iter().map(|r| r * r).sum::<f64>()
+        - 3.0 * nf * (kf + 1.0);
+    let denominator = 1.0 - tie_correction / (nf * kf * (kf * kf - 1.0));
+    if denominator > 0.0 {
+        statistic /= denominator;
+    }
+    let p_value = if k > 1 && n > 0 { chi_square_upper_p(statistic, k - 1) } else { 1.0 };
+
+    FriedmanResult {
+        solvers: solvers.to_vec(),
+        blocks: n,
+        mean_ranks,
+        statistic,
+        p_value,
+        critical_difference: nemenyi_q(k) * (kf * (kf + 1.0) / (6.0 * nf)).sqrt(),
+    }
+}
+
+// Conover post-hoc after a Friedman test, as used by F-Race: returns the indices of the
+// solvers whose rank sum is not significantly worse than the best one
+pub fn conover_survivors(blocks: &[Vec<f64>], alpha: f64) -> Vec<usize> {
+    let n = blocks.len();
+    let k = if n > 0 { blocks[0].len() } else { 0 };
+    if n < 2 || k < 2 {
+        return (0..k).collect();
+    }
+    let mut rank_sums = vec![0.0; k];
+    let mut sum_sq_ranks = 0.0;
+    for block in blocks {
+        let ranks = average_ranks(block);
+        for s in 0..k {
+            rank_sums[s] += ranks[s];
+            sum_sq_ranks += ranks[s] * ranks[s];
+        }
+    }
+
+    let kf = k as f64;
+    let nf = n as f64;
+    let c = nf * kf * (kf + 1.0) * (kf + 1.0) / 4.0;
+    let sum_sq_sums: f64 = rank_sums.iter().map(|r| r * r).sum();
+    let denominator = sum_sq_ranks - c;
+    if denominator <= 0.0 {
+        return (0..k).collect();
+    }
+    let statistic = (kf - 1.0) * (sum_sq_sums - nf * c) / denominator;
+    if chi_square_upper_p(statistic, k - 1) >= alpha {
+        return (0..k).collect();
+    }
+
+    let degrees_of_freedom = (nf - 1.0) * (kf - 1.0);
+    let margin = t_quantile(1.0 - alpha / 2.0, degrees_of_freedom)
+        * ((2.0 * nf * (1.0 - statistic / (nf * (kf - 1.0))) * (sum_sq_ranks - sum_sq_sums / nf)
+            / degrees_of_freedom).max(0.0)).sqrt();
+    let best = rank_sums.iter().cloned().fold(f64::INFINITY, f64::min);
+    (0..k).filter(|&s| rank_sums[s] - best <= margin).collect()
+}
+
+// Friedman test using the mean evaluation of each solver on each instance as a block
+pub fn friedman_by_instance(records: &[RunRecord]) -> FriedmanResult {
+    let names = solvers(records);
+    let summaries = summarize(records, &HashMap::new());
+    let mut instances: Vec<String> = vec![];
+    for s in &summaries {
+        if !instances.contains(&s.instance) {
+            instances.push(s.instance.clone());
+        }
+    }
+
+    let mut blocks = vec![];
+    for instance in instances {
+        let block: Vec<f64> = names.iter()
+            .filter_map(|solver| summaries.iter()
+                .find(|s| s.instance == instance && &s.solver == solver)
+                .map(|s| s.mean))
+            .collect();
+        if block.len() == names.len() {
+            blocks.push(block);
+        }
+    }
+    friedman(&names, &blocks)
+}
+
+pub fn report(records: &[RunRecord], best_known: &HashMap<String, i64>) -> String {
+    let mut text = String::new();
+    text.push_str(&format!("{:<24} {:<10} {:>5} {:>10} {:>12} {:>12} {:>10} {:>9} {:>14}\n",
+        "instancia", "metodo", "runs", "melhor", "media", "mediana", "desvio", "gap(%)", "tempo medio"));
+    for s in summarize(records, best_known) {
+        let gap = match s.mean_gap {
+            Some(g) => format!("{:.2}", g),
+            None => "-".to_string(),
+        };
+        text.push_str(&format!("{:<24} {:<10} {:>5} {:>10} {:>12.2} {:>12.2} {:>10.2} {:>9} {:>14?}\n",
+            s.instance, s.solver, s.runs, s.best, s.mean, s.median, s.std, gap, s.mean_time));
+    }
+
+    text.push_str("\nWilcoxon (pareado por instancia e execucao):\n");
+    for (a, b, w) in pairwise_wilcoxon(records) {
+        text.push_str(&format!("{} x {}: n = {}, W+ = {:.1}, W- = {:.1}, z = {:.3}, p = {:.4}\n",
+            a, b, w.n, w.w_plus, w.w_minus, w.z, w.p_value));
+    }
+
+    let f = friedman_by_instance(records);
+    if f.blocks == 0 {
+        text.push_str("\nFriedman: nenhuma instancia com resultados de todos os metodos\n");
+        return text;
+    }
+    text.push_str(&format!("\nFriedman: {} blocos, chi2 = {:.3}, p = {:.4}, CD (Nemenyi, 0.05) = {:.3}\n",
+        f.blocks, f.statistic, f.p_value, f.critical_difference));
+    let mut order: Vec<usize> = (0..f.solvers.len()).collect();
+    order.sort_by(|&a, &b| f.mean_ranks[a].total_cmp(&f.mean_ranks[b]));
+    for i in order {
+        text.push_str(&format!("{}: rank medio {:.3}\n", f.solvers[i], f.mean_ranks[i]));
+    }
+    text
+}
@@ -0,0 +1,318 @@
+use crate::{Cost, Instance};
+
+pub struct HeldKarpBound {
+    // Best Lagrangian value found, rounded up since the distances are integer
+    pub value: Cost,
+    // Node potentials at the best value; d(i,j) + pi[i] + pi[j] are the reduced costs
+    // that alpha-nearness candidate sets are computed from
+    pub potentials: Vec<f64>,
+    pub iterations: usize,
+    // The best 1-tree was a tour, so the bound is the optimum
+    pub is_tour: bool,
+}
+
+impl Instance {
+    // Minimum 1-tree under the potentials: MST over cities 1..n plus the two cheapest edges
+    // of city 0. Returns its cost (without subtracting 2 * sum(pi)) and the degree of each city
+    pub fn one_tree(&self, potentials: &[f64]) -> (f64, Vec<i32>) {
+        let n = self.size();
+        let cost = |i: usize, j: usize| self.undirected_distance(i, j) as f64 + potentials[i] + potentials[j];
+        let mut degrees = vec![0; n];
+        let mut total = 0.0;
+
+        let mut in_tree = vec![false; n];
+        let mut key = vec![f64::INFINITY; n];
+        let mut parent = vec![usize::MAX; n];
+        key[1] = 0.0;
+        for _ in 1..n {
+            let mut chosen = usize::MAX;
+            for c in 1..n {
+                if !in_tree[c] && (chosen == usize::MAX || key[c] < key[chosen]) {
+                    chosen = c;
+                }
+            }
+            in_tree[chosen] = true;
+            if parent[chosen] != usize::MAX {
+                total += key[chosen];
+                degrees[chosen] += 1;
+                degrees[parent[chosen]] += 1;
+            }
+            for c in 1..n {
+                if !in_tree[c] {
+                    let edge = cost(chosen, c);
+                    if edge < key[c] {
+                        key[c] = edge;
+                        parent[c] = chosen;
+                    }
+                }
+            }
+        }
+
+        let mut first = (f64::INFINITY, 0);
+        let mut second = (f64::INFINITY, 0);
+        for c in 1..n {
+            let edge = cost(0, c);
+            if edge < first.0 {
+                second = first;
+                first = (edge, c);
+            } else if edge < second.0 {
+                second = (edge, c);
+            }
+        }
+        total += first.0 + second.0;
+        degrees[0] = 2;
+        degrees[first.1] += 1;
+        degrees[second.1] += 1;
+        (total, degrees)
+    }
+
+    // Held-Karp bound by subgradient ascent on the node potentials (Held & Karp, 1971).
+    // `upper_bound` is the cost of a known tour, used to size the steps
+    pub fn held_karp_bound(&self, upper_bound: Cost, max_iterations: usize) -> HeldKarpBound {
+        let n = self.size();
+        // The only tour; distances alone, as the penalties are bounded separately
+        if n < 3 {
+            let tour = self.sequential();
+            return HeldKarpBound {
+                value: self.tour_distance(&tour),
+                potentials: vec![0.0; n],
+                iterations: 0,
+                is_tour: true,
+            };
+        }
+
+        let mut potentials = vec![0.0; n];
+        let mut best = HeldKarpBound { value: Cost::MIN, potentials: potentials.clone(), iterations: 0, is_tour: false };
+        let mut best_value = f64::NEG_INFINITY;
+        let mut lambda = 2.0;
+        let mut without_improvement = 0;
+        let patience = (n / 2).clamp(10, 100);
+
+        for iteration in 0..max_iterations {
+            let (tree_cost, degrees) = self.one_tree(&potentials);
+            let value = tree_cost - 2.0 * potentials.iter().sum::<f64>();
+            let norm: f64 = degrees.iter().map(|&d| ((d - 2) * (d - 2)) as f64).sum();
+
+            if value > best_value + 1e-9 {
+                best_value = value;
+                best.value = (value - 1e-6).ceil() as Cost;
+                best.potentials = potentials.clone();
+                best.iterations = iteration + 1;
+                without_improvement = 0;
+            } else {
+                without_improvement += 1;
+                if without_improvement >= patience {
+                    lambda /= 2.0;
+                    without_improvement = 0;
+                }
+            }
+            // On asymmetric instances the 1-tree is built on the cheaper direction of each edge,
+            // so a tour there need not be a directed optimum
+            if norm == 0.0 {
+                best.is_tour = self.symmetric;
+                break;
+            }
+            if lambda < 1e-6 || best.value >= upper_bound {
+                break;
+            }
+
+            let step = lambda * (upper_bound as f64 - value).max(1.0) / norm;
+            for i in 0..n {
+                potentials[i] += step * (degrees[i] - 2) as f64;
+            }
+        }
+        best
+    }
+}
+
+pub struct TsppBound {
+    pub value: Cost,
+    // Held-Karp bound on the distances alone
+    pub distance: Cost,
+    // Optimal assignment of cities to positions on the penalties alone
+    pub assignment: Cost,
+    // Lagrangian n-path relaxation over (city, position), if it was run
+    pub lagrangian: Option<Cost>,
+}
+
+// Hungarian algorithm (shortest augmenting paths with potentials), O(n^3). Returns the minimum
+// cost and, for each row, the column assigned to it
+pub fn assignment(costs: &[Vec<Cost>]) -> (Cost, Vec<usize>) {
+    let n = costs.len();
+    let mut u: Vec<Cost> = vec![0; n + 1];
+    let mut v: Vec<Cost> = vec![0; n + 1];
+    let mut row_of = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_to = vec![Cost::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = Cost::MAX;
+            let mut j1 = 0;
+            for j in 1..=n {
+                if !used[j] {
+                    let reduced = costs[i0-1][j-1] - u[i0] - v[j];
+                    if reduced < min_to[j] {
+                        min_to[j] = reduced;
+                        way[j] = j0;
+                    }
+                    if min_to[j] < delta {
+                        delta = min_to[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_to[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut column_of = vec![0; n];
+    let mut total = 0;
+    for j in 1..=n {
+        column_of[row_of[j]-1] = j - 1;
+        total += costs[row_of[j]-1][j-1];
+    }
+    (total, column_of)
+}
+
+impl Instance {
+    // Walk of n positions minimizing distances plus matrix[city][k] - multipliers[city], where
+    // cities may repeat (but not twice in a row). The closing edge is bounded below by the mean
+    // of the cheapest edges into the first city and out of the last, so the DP does not need to
+    // fix the first city. Returns the relaxed cost and how many times each city was visited
+    fn n_path(&self, multipliers: &[f64], cheapest_in: &[f64], cheapest_out: &[f64]) -> (f64, Vec<i32>) {
+        let n = self.size();
+        let node_cost = |c: usize, k: usize| self.matrix[c][k] as f64 - multipliers[c];
+        let mut cost: Vec<f64> = (0..n).map(|c| node_cost(c, 0) + cheapest_in[c] / 2.0).collect();
+        let mut parents: Vec<Vec<usize>> = vec![vec![0; n]; n];
+
+        for (k, parent) in parents.iter_mut().enumerate().skip(1) {
+            let mut next_cost = vec![f64::INFINITY; n];
+            for j in 0..n {
+                let mut best_here = (f64::INFINITY, usize::MAX);
+                for (i, &cost_i) in cost.iter().enumerate() {
+                    if i != j {
+                        let through = cost_i + self.distance(i, j) as f64;
+                        if through < best_here.0 {
+                            best_here = (through, i);
+                        }
+                    }
+                }
+                next_cost[j] = best_here.0 + node_cost(j, k);
+                parent[j] = best_here.1;
+            }
+            cost = next_cost;
+        }
+
+        let mut end = 0;
+        let mut total = f64::INFINITY;
+        for j in 0..n {
+            let closed = cost[j] + cheapest_out[j] / 2.0;
+            if closed < total {
+                total = closed;
+                end = j;
+            }
+        }
+        let mut visits = vec![0; n];
+        let mut current = end;
+        for k in (0..n).rev() {
+            visits[current] += 1;
+            if k > 0 {
+                current = parents[k][current];
+            }
+        }
+        (total, visits)
+    }
+
+    // Subgradient ascent on the multipliers of the "each city exactly once" constraints of
+    // the n-path relaxation, which couples the distances with the positional penalties
+    pub fn n_path_bound(&self, upper_bound: Cost, max_iterations: usize) -> Cost {
+        let n = self.size();
+        let cheapest_in: Vec<f64> = (0..n)
+            .map(|c| (0..n).filter(|&o| o != c).map(|o| self.distance(o, c)).min().unwrap_or(0) as f64)
+            .collect();
+        let cheapest_out: Vec<f64> = (0..n)
+            .map(|c| (0..n).filter(|&o| o != c).map(|o| self.distance(c, o)).min().unwrap_or(0) as f64)
+            .collect();
+        let mut multipliers = vec![0.0; n];
+        let mut best_value = f64::NEG_INFINITY;
+        let mut lambda = 2.0;
+        let mut without_improvement = 0;
+
+        for _ in 0..max_iterations {
+            let (walk_cost, visits) = self.n_path(&multipliers, &cheapest_in, &cheapest_out);
+            let value = walk_cost + multipliers.iter().sum::<f64>();
+            if value > best_value + 1e-9 {
+                best_value = value;
+                without_improvement = 0;
+            } else {
+                without_improvement += 1;
+                if without_improvement >= 10 {
+                    lambda /= 2.0;
+                    without_improvement = 0;
+                }
+            }
+            let norm: f64 = visits.iter().map(|&v| ((1 - v) * (1 - v)) as f64).sum();
+            if norm == 0.0 || lambda < 1e-6 || best_value >= upper_bound as f64 {
+                break;
+            }
+            let step = lambda * (upper_bound as f64 - value).max(1.0) / norm;
+            for c in 0..n {
+                multipliers[c] += step * (1 - visits[c]) as f64;
+            }
+        }
+        (best_value - 1e-6).ceil() as Cost
+    }
+
+    // Lower bound for the objective with positional penalties: the larger of the sum of the
+    // distance (Held-Karp) and assignment relaxations and the Lagrangian n-path bound
+    pub fn tspp_bound(&self, upper_bound: Cost, max_iterations: usize, path_iterations: usize) -> TsppBound {
+        let distance = self.held_karp_bound(upper_bound, max_iterations).value;
+        let (assignment, _) = assignment(&self.matrix);
+        let lagrangian = if path_iterations > 0 {
+            Some(self.n_path_bound(upper_bound, path_iterations))
+        } else {
+            None
+        };
+        TsppBound {
+            value: (distance + assignment).max(lagrangian.unwrap_or(Cost::MIN)),
+            distance,
+            assignment,
+            lagrangian,
+        }
+    }
+    // Bound reported next to a solution: Held-Karp without penalties, the TSPP bound with them.
+    // The n-path relaxation costs O(n^3) per iteration, so it only runs on small instances
+    pub fn lower_bound(&self, upper_bound: Cost) -> Cost {
+        let max_iterations = 10 * self.size();
+        if self.matrix.is_empty() {
+            self.held_karp_bound(upper_bound, max_iterations).value
+        } else {
+            let path_iterations = if self.size() <= 200 { 300 } else { 0 };
+            self.tspp_bound(upper_bound, max_iterations, path_iterations).value
+        }
+    }
+}
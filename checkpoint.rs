@@ -0,0 +1,152 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::*;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    pub instance: String,
+    pub solver: String,
+    pub config: String,
+    pub seed: u64,
+    pub eval: i64,
+    pub time: Duration,
+    pub tour: Vec<usize>,
+}
+
+impl JournalEntry {
+    fn to_line(&self) -> String {
+        let tour: Vec<String> = self.tour.iter().map(|c| c.to_string()).collect();
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n", self.instance, self.solver, self.config, self.seed,
+            self.eval, self.time.as_nanos(), tour.join(" "))
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            return None;
+        }
+        let tour: Option<Vec<usize>> = fields[6].split_whitespace().map(|c| c.parse().ok()).collect();
+        Some(Self {
+            instance: fields[0].to_string(),
+            solver: fields[1].to_string(),
+            config: fields[2].to_string(),
+            seed: fields[3].parse().ok()?,
+            eval: fields[4].parse().ok()?,
+            time: Duration::from_nanos(fields[5].parse().ok()?),
+            tour: tour?,
+        })
+    }
+}
+
+// Append-only record of the finished (instance, solver, config, seed) cells of a batch.
+// A line is only considered once it is complete, so a crash in the middle of a write
+// just makes that cell run again.
+pub struct Journal {
+    file: File,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn open(path: &str) -> Self {
+        let mut entries = vec![];
+        if Path::new(path).exists() {
+            let mut content = String::new();
+            File::open(path)
+                .expect("Falha ao abrir o journal")
+                .read_to_string(&mut content)
+                .expect("Falha ao ler o journal");
+            for line in content.split_inclusive('\n') {
+                if let Some(line) = line.strip_suffix('\n') {
+                    if let Some(entry) = JournalEntry::from_line(line) {
+                        entries.push(entry);
+                    }
+                }
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .expect("Falha ao abrir o journal");
+        Self { file, entries }
+    }
+
+    pub fn find(&self, instance: &str, solver: &str, config: &str, seed: u64) -> Option<&JournalEntry> {
+        self.entries.iter().find(|e| e.instance == instance && e.solver == solver
+            && e.config == config && e.seed == seed)
+    }
+
+    pub fn record(&mut self, entry: JournalEntry) {
+        match self.file.write_all(entry.to_line().as_bytes()).and_then(|_| self.file.sync_data()) {
+            Ok(()) => (),
+            Err(_) => panic!("Nao consegui escrever no journal"),
+        }
+        self.entries.push(entry);
+    }
+}
+
+// State of a long single run: the iteration to resume from, the tours it keeps
+// (e.g. current and best) and any numeric state such as the pheromone matrix
+pub struct Snapshot {
+    pub iteration: usize,
+    pub tours: Vec<Vec<usize>>,
+    pub values: Vec<f64>,
+}
+
+fn read_u64(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let chunk = bytes.get(*pos..*pos + 8)?;
+    *pos += 8;
+    Some(u64::from_le_bytes(chunk.try_into().ok()?))
+}
+
+impl Snapshot {
+    // Written to a temporary file first and renamed, so an interrupted save keeps the previous one
+    pub fn save(&self, path: &str) {
+        let mut bytes: Vec<u8> = vec![];
+        bytes.extend_from_slice(&(self.iteration as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.tours.len() as u64).to_le_bytes());
+        for tour in &self.tours {
+            bytes.extend_from_slice(&(tour.len() as u64).to_le_bytes());
+            for &city in tour {
+                bytes.extend_from_slice(&(city as u64).to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&(self.values.len() as u64).to_le_bytes());
+        for value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let tmp_path = format!("{}.tmp", path);
+        let result = File::create(&tmp_path)
+            .and_then(|mut file| file.write_all(&bytes).and_then(|_| file.sync_all()))
+            .and_then(|_| fs::rename(&tmp_path, path));
+        match result {
+            Ok(()) => (),
+            Err(err) => panic!("Erro ao salvar o snapshot {}: {}", path, err),
+        }
+    }
+
+    pub fn load(path: &str) -> Option<Self> {
+        let bytes = fs::read(path).ok()?;
+        let mut pos = 0;
+        let iteration = read_u64(&bytes, &mut pos)? as usize;
+        let n_tours = read_u64(&bytes, &mut pos)? as usize;
+        let mut tours = vec![];
+        for _ in 0..n_tours {
+            let len = read_u64(&bytes, &mut pos)? as usize;
+            let mut tour = vec![];
+            for _ in 0..len {
+                tour.push(read_u64(&bytes, &mut pos)? as usize);
+            }
+            tours.push(tour);
+        }
+        let n_values = read_u64(&bytes, &mut pos)? as usize;
+        let mut values = vec![];
+        for _ in 0..n_values {
+            values.push(f64::from_bits(read_u64(&bytes, &mut pos)?));
+        }
+        Some(Self { iteration, tours, values })
+    }
+
+    pub fn remove(path: &str) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use tsp::{mttsp, read_file, Cost, Instance, EXACT_SCALE};
+use tsp::analysis::{self, RunRecord};
+use tsp::args::{take_flag, take_option};
+use tsp::checkpoint::{Journal, JournalEntry};
+use tsp::metaheuristics;
+use tsp::metrics::Metric;
+use tsp::report::{Format, SolveReport};
+use tsp::solution::{check_permutation, read_tour, RunStats};
+use tsp::solver::{Budget, Params};
+use tsp::tsplib::write_tour;
+
+const USAGE: &str = "Uso: tsp <comando> [argumentos]
+  solve <metodo> <instancia> [multas] [--params p=v,...] [--seed N] [--iterations N] [--time-limit S] [--output tour] [--format text|json] [--bound]
+  bench <especificacao>
+  generate <tsp|tspp|mttsp> <n> <saida> [--seed N] [--side L] [--max-penalty M] [--agent-speed V] [--max-speed V]
+  verify <instancia> <tour|resultado.json> [multas] [--expected custo]
+  convert <entrada> <saida.tsp|.atsp|.tspp|.mat|.lp|.mps|.mttsp> [multas]
+  info <instancia> [multas]
+Instancias .mttsp sao do TSP com alvos moveis; as demais, do TSP e do TSP com multas.
+Opcoes de todos os comandos: --threads N; de instancias TSP: --metric m, --exact";
+
+// Instance of either problem, told apart by the extension of the file
+enum Problem {
+    Tsp(Instance),
+    MovingTarget(mttsp::Instance),
+}
+
+// A solver run, with the costs in units of 1/scale
+struct Outcome {
+    tour: Vec<usize>,
+    distance: Cost,
+    penalty: Cost,
+    stats: RunStats,
+    time: Duration,
+}
+
+// Options that change how a TSP instance is read
+struct ReadOptions {
+    metric: Option<Metric>,
+    exact: bool,
+}
+
+impl ReadOptions {
+    fn take(args: &mut Vec<String>) -> Self {
+        Self {
+            metric: take_option(args, "--metric").map(|name| Metric::from_name(&name)),
+            exact: take_flag(args, "--exact"),
+        }
+    }
+}
+
+impl Problem {
+    fn load(file_name: &str, matrix_file_name: Option<&str>, options: &ReadOptions) -> Problem {
+        if file_name.ends_with(".mttsp") {
+            if matrix_file_name.is_some() {
+                panic!("Instancias MTTSP nao tem matriz de multas");
+            }
+            let mut instance = mttsp::Instance::new();
+            instance.set_data(file_name);
+            return Problem::MovingTarget(instance);
+        }
+        let mut instance = Instance::new();
+        if let Some(metric) = options.metric {
+            instance.metric = metric;
+        }
+        if options.exact {
+            instance.scale = EXACT_SCALE;
+        }
+        instance.set_data(file_name, matrix_file_name);
+        Problem::Tsp(instance)
+    }
+
+    fn format_cost(&self, cost: Cost) -> String {
+        match self {
+            Problem::Tsp(instance) => instance.format_cost(cost),
+            Problem::MovingTarget(_) => cost.to_string(),
+        }
+    }
+
+    fn cost_value(&self, cost: Cost) -> f64 {
+        match self {
+            Problem::Tsp(instance) => instance.cost_value(cost),
+            Problem::MovingTarget(_) => cost as f64,
+        }
+    }
+
+    fn has_penalties(&self) -> bool {
+        match self {
+            Problem::Tsp(instance) => !instance.matrix.is_empty(),
+            Problem::MovingTarget(_) => false,
+        }
+    }
+
+    fn validate(&self, tour: &Vec<usize>) -> Result<(), String> {
+        match self {
+            Problem::Tsp(instance) => instance.validate(tour),
+            Problem::MovingTarget(instance) => check_permutation(tour, instance.targets.len()),
+        }
+    }
+
+    // Distance and penalty of a valid tour
+    fn costs(&self, tour: &[usize]) -> (Cost, Cost) {
+        match self {
+            Problem::Tsp(instance) => (instance.tour_distance(tour), instance.tour_penalty(tour)),
+            Problem::MovingTarget(instance) => (instance.evaluate(tour), 0),
+        }
+    }
+
+    // Lower bound on the optimum, not available for MTTSP
+    fn lower_bound(&self, upper_bound: Cost) -> Option<Cost> {
+        match self {
+            Problem::Tsp(instance) => Some(instance.lower_bound(upper_bound)),
+            Problem::MovingTarget(_) => None,
+        }
+    }
+
+    fn solve(&self, method: &str, params: &Params, seed: u64, budget: &Budget) -> Outcome {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let start = Instant::now();
+        let (tour, stats) = match self {
+            Problem::Tsp(instance) => {
+                let registry = metaheuristics::registry();
+                let solver = registry.build(method, params).unwrap_or_else(||
+                    panic!("Metodo desconhecido: {} (disponiveis: {})", method, registry.names().join(", ")));
+                let solved = solver.solve(instance, &mut rng, budget);
+                (instance.best_rotation(&solved.tour), solved.stats)
+            },
+            Problem::MovingTarget(instance) => {
+                let registry = mttsp::registry();
+                let solver = registry.build(method, params).unwrap_or_else(||
+                    panic!("Metodo desconhecido para MTTSP: {} (disponiveis: {})", method, registry.names().join(", ")));
+                let solved = solver.solve(instance, &mut rng, budget);
+                (solved.tour, solved.stats)
+            },
+        };
+        let time = start.elapsed();
+        if let Err(message) = self.validate(&tour) {
+            panic!("Solucao invalida: {}", message);
+        }
+        let (distance, penalty) = self.costs(&tour);
+        Outcome { tour, distance, penalty, stats, time }
+    }
+}
+
+fn take_seed(args: &mut Vec<String>) -> u64 {
+    match take_option(args, "--seed") {
+        Some(value) => value.parse().expect("Seed invalida"),
+        None => rand::thread_rng().gen(),
+    }
+}
+
+fn take_budget(args: &mut Vec<String>) -> Budget {
+    Budget {
+        iterations: take_option(args, "--iterations").map(|value| value.parse().expect("Numero de iteracoes invalido")),
+        time: take_option(args, "--time-limit")
+            .map(|value| Duration::from_secs_f64(value.parse().expect("Limite de tempo invalido"))),
+    }
+}
+
+// Name of the instance in reports and generated files
+fn stem(file_name: &str) -> String {
+    Path::new(file_name).file_stem().map_or(file_name.to_string(), |stem| stem.to_string_lossy().to_string())
+}
+
+// Instance of a bench run in the journal and the report. The same file with different penalty
+// matrices gives different instances
+fn instance_key(file_name: &str, matrix_file_name: Option<&str>) -> String {
+    match matrix_file_name {
+        Some(matrix_file_name) => format!("{}+{}", file_name, matrix_file_name),
+        None => file_name.to_string(),
+    }
+}
+
+fn solve(mut args: Vec<String>) {
+    let seed = take_seed(&mut args);
+    let params = Params::parse(&take_option(&mut args, "--params").unwrap_or_default());
+    let budget = take_budget(&mut args);
+    let output_file = take_option(&mut args, "--output");
+    let format = take_option(&mut args, "--format").map_or(Format::Text, |name| Format::from_name(&name));
+    let report_bound = take_flag(&mut args, "--bound");
+    let options = ReadOptions::take(&mut args);
+    if args.len() < 3 {
+        println!("{}", USAGE);
+        return;
+    }
+    let problem = Problem::load(&args[2], args.get(3).map(|name| name.as_str()), &options);
+
+    if format == Format::Text {
+        println!("Seed: {}", seed);
+    }
+    let outcome = problem.solve(&args[1], &params, seed, &budget);
+    let cost = outcome.distance + outcome.penalty;
+    let bound = if report_bound { problem.lower_bound(cost) } else { None };
+    let gap = bound.map(|bound| 100.0 * (cost - bound) as f64 / bound as f64);
+    if let Some(file_name) = &output_file {
+        write_tour(file_name, &stem(&args[2]), &outcome.tour);
+    }
+    if format == Format::Json {
+        let report = SolveReport {
+            instance: args[2].clone(),
+            solver: args[1].clone(),
+            params,
+            seed,
+            tour: outcome.tour,
+            cost: problem.cost_value(cost),
+            distance: problem.cost_value(outcome.distance),
+            penalty: problem.cost_value(outcome.penalty),
+            time: outcome.time.as_secs_f64(),
+            iterations: Some(outcome.stats.iterations),
+            evaluations: Some(outcome.stats.evaluations),
+            bound: bound.map(|bound| problem.cost_value(bound)),
+            gap,
+            proof: outcome.stats.proof,
+        };
+        println!("{}", report.to_json());
+        return;
+    }
+    println!("{:?}", outcome.tour);
+    println!("{}", problem.format_cost(cost));
+    if problem.has_penalties() {
+        println!("Distancia: {}, multas: {}", problem.format_cost(outcome.distance), problem.format_cost(outcome.penalty));
+    }
+    println!("Iteracoes: {}, avaliacoes: {}", outcome.stats.iterations, outcome.stats.evaluations);
+    if let (Some(proof), Problem::Tsp(instance)) = (outcome.stats.proof, &problem) {
+        println!("{}", instance.format_proof(&proof));
+    }
+    println!("Tempo de execucao: {:?}", outcome.time);
+    if let (Some(bound), Some(gap)) = (bound, gap) {
+        println!("Limite inferior: {}, gap: {:.2}%", problem.format_cost(bound), gap);
+    } else if report_bound {
+        println!("Limite inferior nao disponivel para MTTSP");
+    }
+    if let Some(file_name) = output_file {
+        println!("Tour escrito em {}", file_name);
+    }
+}
+
+// Experiment read from a specification file, one "key values" per line:
+//   instance <arquivo> [multas]     (one line per instance)
+//   solver <metodo> [p=v,...]       (one line per solver configuration)
+//   seeds <N> | seeds <s1> <s2> ... (N runs with seeds 0..N, or the given seeds)
+//   iterations <N>, time-limit <S>  (budget of every run)
+//   journal <arquivo>               (finished runs are recorded and skipped when restarted)
+//   output <arquivo>                (where the report is also written)
+struct BenchSpec {
+    instances: Vec<(String, Option<String>)>,
+    solvers: Vec<(String, String)>,
+    seeds: Vec<u64>,
+    budget: Budget,
+    journal: Option<String>,
+    output: Option<String>,
+}
+
+impl BenchSpec {
+    fn read(file_name: &str) -> Self {
+        let mut spec = BenchSpec { instances: vec![], solvers: vec![], seeds: (0..10).collect(), budget: Budget::default(), journal: None, output: None };
+        for line in read_file(file_name).lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.is_empty() || tokens[0].starts_with('#') {
+                continue;
+            }
+            let value = |k: usize| tokens.get(k).unwrap_or_else(|| panic!("Valor nao especificado para {}", tokens[0])).to_string();
+            match tokens[0] {
+                "instance" => spec.instances.push((value(1), tokens.get(2).map(|name| name.to_string()))),
+                "solver" => spec.solvers.push((value(1), tokens[2..].join(","))),
+                "seeds" if tokens.len() == 2 => spec.seeds = (0..value(1).parse().expect("Numero de seeds invalido")).collect(),
+                "seeds" => spec.seeds = tokens[1..].iter().map(|t| t.parse().expect("Seed invalida")).collect(),
+                "iterations" => spec.budget.iterations = Some(value(1).parse().expect("Numero de iteracoes invalido")),
+                "time-limit" => spec.budget.time = Some(Duration::from_secs_f64(value(1).parse().expect("Limite de tempo invalido"))),
+                "journal" => spec.journal = Some(value(1)),
+                "output" => spec.output = Some(value(1)),
+                other => panic!("Chave desconhecida na especificacao: {}", other),
+            }
+        }
+        spec
+    }
+}
+
+fn bench(mut args: Vec<String>) {
+    let options = ReadOptions::take(&mut args);
+    if args.len() < 2 {
+        println!("{}", USAGE);
+        return;
+    }
+    let spec = BenchSpec::read(&args[1]);
+    let journal = spec.journal.as_deref().map(|path| Mutex::new(Journal::open(path)));
+    let mut records: Vec<RunRecord> = vec![];
+    for (file_name, matrix_file_name) in &spec.instances {
+        let problem = Problem::load(file_name, matrix_file_name.as_deref(), &options);
+        let name = instance_key(file_name, matrix_file_name.as_deref());
+        for (method, config) in &spec.solvers {
+            let params = Params::parse(config);
+            let label = if config.is_empty() { method.clone() } else { format!("{}[{}]", method, config) };
+            // The runs of a configuration are independent, each depending only on its seed
+            let runs: Vec<JournalEntry> = spec.seeds.par_iter().map(|&seed| {
+                if let Some(journal) = &journal {
+                    if let Some(entry) = journal.lock().unwrap().find(&name, method, config, seed) {
+                        println!("{} seed {} ja executado ({}), pulando", label, seed, name);
+                        return entry.clone();
+                    }
+                }
+                let outcome = problem.solve(method, &params, seed, &spec.budget);
+                let entry = JournalEntry {
+                    instance: name.clone(),
+                    solver: method.clone(),
+                    config: config.clone(),
+                    seed,
+                    eval: outcome.distance + outcome.penalty,
+                    time: outcome.time,
+                    tour: outcome.tour,
+                };
+                println!("{} {} seed {}: {} ({:?})", name, label, seed, problem.format_cost(entry.eval), entry.time);
+                if let Some(journal) = &journal {
+                    journal.lock().unwrap().record(entry.clone());
+                }
+                entry
+            }).collect();
+            for (run, entry) in runs.into_iter().enumerate() {
+                records.push(RunRecord { instance: name.clone(), solver: label.clone(), run, eval: entry.eval, time: entry.time });
+            }
+        }
+    }
+
+    // Gaps against the best tour found for each instance
+    let mut best_known: HashMap<String, i64> = HashMap::new();
+    for r in &records {
+        let best = best_known.entry(r.instance.clone()).or_insert(r.eval);
+        if r.eval < *best {
+            *best = r.eval;
+        }
+    }
+    let report = analysis::report(&records, &best_known);
+    println!("{}", report);
+    if let Some(file_name) = spec.output {
+        let mut file = File::create(&file_name).expect("Falha ao criar o arquivo");
+        file.write_all(report.as_bytes()).expect("Nao consegui escrever no arquivo");
+    }
+}
+
+fn generate(mut args: Vec<String>) {
+    let mut rng = StdRng::seed_from_u64(take_seed(&mut args));
+    let side: f64 = take_option(&mut args, "--side").map_or(1000.0, |value| value.parse().expect("Lado invalido"));
+    let max_penalty: Cost = take_option(&mut args, "--max-penalty").map_or(200, |value| value.parse().expect("Multa maxima invalida"));
+    let agent_speed: f64 = take_option(&mut args, "--agent-speed").map_or(100.0, |value| value.parse().expect("Velocidade invalida"));
+    let max_speed: f64 = take_option(&mut args, "--max-speed").map_or(10.0, |value| value.parse().expect("Velocidade invalida"));
+    if args.len() < 4 {
+        println!("{}", USAGE);
+        return;
+    }
+    let n: usize = args[2].parse().expect("Numero de cidades invalido");
+    let output = args[3].as_str();
+    match args[1].as_str() {
+        "tsp" | "tspp" => {
+            let mut instance = Instance::random(n, side, &mut rng);
+            instance.write_cities(output, &stem(output));
+            println!("Instancia escrita em {}", output);
+            if args[1] == "tspp" {
+                let matrix_file_name = Path::new(output).with_extension("mat").to_string_lossy().to_string();
+                instance.random_penalties(max_penalty, &mut rng);
+                instance.write_penalties(&matrix_file_name);
+                println!("Multas escritas em {}", matrix_file_name);
+            }
+        },
+        "mttsp" => {
+            if max_speed >= agent_speed {
+                panic!("A velocidade maxima dos alvos deve ser menor que a do agente");
+            }
+            mttsp::Instance::random(n, side, agent_speed, max_speed, &mut rng).write(output);
+            println!("Instancia escrita em {}", output);
+        },
+        other => panic!("Tipo de instancia desconhecido: {}", other),
+    }
+}
+
+fn verify(mut args: Vec<String>) {
+    let mut expected: Option<f64> = take_option(&mut args, "--expected").map(|value| value.parse().expect("Custo esperado invalido"));
+    let options = ReadOptions::take(&mut args);
+    if args.len() < 3 {
+        println!("{}", USAGE);
+        return;
+    }
+    let problem = Problem::load(&args[1], args.get(3).map(|name| name.as_str()), &options);
+    // A result written by solve --format json is checked against the cost it reports
+    let tour = if args[2].ends_with(".json") {
+        let report = SolveReport::read(&args[2]);
+        expected = expected.or(Some(report.cost));
+        report.tour
+    } else {
+        read_tour(&read_file(&args[2]))
+    };
+    if let Err(message) = problem.validate(&tour) {
+        println!("Tour invalido: {}", message);
+        process::exit(1);
+    }
+    let (distance, penalty) = problem.costs(&tour);
+    println!("Tour valido");
+    println!("{}", problem.format_cost(distance + penalty));
+    if problem.has_penalties() {
+        println!("Distancia: {}, multas: {}", problem.format_cost(distance), problem.format_cost(penalty));
+    }
+    if let Some(value) = expected {
+        let scale = match &problem {
+            Problem::Tsp(instance) => instance.scale,
+            Problem::MovingTarget(_) => 1,
+        };
+        let expected = (value * scale as f64).round() as Cost;
+        if expected != distance + penalty {
+            println!("Custo diferente do esperado ({})", problem.format_cost(expected));
+            process::exit(1);
+        }
+    }
+}
+
+fn convert(mut args: Vec<String>) {
+    let options = ReadOptions::take(&mut args);
+    if args.len() < 3 {
+        println!("{}", USAGE);
+        return;
+    }
+    let output = args[2].as_str();
+    let extension = Path::new(output).extension().map_or(String::new(), |e| e.to_string_lossy().to_lowercase());
+    match (Problem::load(&args[1], args.get(3).map(|name| name.as_str()), &options), extension.as_str()) {
+        (Problem::Tsp(instance), "tsp" | "atsp") => instance.write_tsplib(output, &stem(output)),
+        (Problem::Tsp(instance), "tspp") => {
+            if instance.cities.len() != instance.size() {
+                panic!("A instancia nao tem coordenadas");
+            }
+            instance.write_cities(output, &stem(output));
+        },
+        (Problem::Tsp(instance), "mat") => instance.write_distances(output),
+        (Problem::Tsp(instance), "lp" | "mps") => {
+            if instance.matrix.is_empty() {
+                panic!("O modelo MILP precisa da matriz de multas");
+            }
+            if extension == "lp" {
+                instance.write_lp(output);
+            } else {
+                instance.write_mps(output);
+            }
+        },
+        (Problem::MovingTarget(instance), "mttsp") => instance.write(output),
+        _ => panic!("Conversao para .{} nao suportada para essa instancia", extension),
+    }
+    println!("Instancia escrita em {}", output);
+}
+
+// Minimum, mean and maximum
+fn spread(values: impl Iterator<Item = f64>) -> (f64, f64, f64) {
+    let (mut min, mut max, mut sum, mut count) = (f64::INFINITY, f64::NEG_INFINITY, 0.0, 0);
+    for value in values {
+        min = min.min(value);
+        max = max.max(value);
+        sum += value;
+        count += 1;
+    }
+    (min, sum / count.max(1) as f64, max)
+}
+
+// Distances over every pair up to this many cities, over a sample of pairs above it
+const INFO_EXACT_CITIES: usize = 3000;
+const INFO_SAMPLE_PAIRS: usize = 1_000_000;
+
+fn info(mut args: Vec<String>) {
+    let options = ReadOptions::take(&mut args);
+    if args.len() < 2 {
+        println!("{}", USAGE);
+        return;
+    }
+    println!("Instancia: {}", args[1]);
+    match Problem::load(&args[1], args.get(2).map(|name| name.as_str()), &options) {
+        Problem::Tsp(instance) => {
+            let n = instance.size();
+            let kind = if !instance.symmetric { "ATSP" } else if instance.matrix.is_empty() { "TSP" } else { "TSP com multas" };
+            println!("Tipo: {}", kind);
+            println!("Cidades: {}", n);
+            println!("Armazenamento das distancias: {:?}", instance.storage());
+            if instance.cities.len() == n && n > 0 {
+                let (min_x, _, max_x) = spread(instance.cities.iter().map(|city| city.x));
+                let (min_y, _, max_y) = spread(instance.cities.iter().map(|city| city.y));
+                println!("Metrica: {:?}", instance.metric);
+                println!("Coordenadas: x em [{}, {}], y em [{}, {}]", min_x, max_x, min_y, max_y);
+            }
+            let scale = instance.scale as f64;
+            let pairs: Vec<(usize, usize)> = if n <= INFO_EXACT_CITIES {
+                (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j))).collect()
+            } else {
+                let mut rng = StdRng::seed_from_u64(0);
+                (0..INFO_SAMPLE_PAIRS).map(|_| (rng.gen_range(0..n), rng.gen_range(0..n))).filter(|(i, j)| i != j).collect()
+            };
+            let (min, mean, max) = spread(pairs.iter().map(|&(i, j)| instance.distance(i, j) as f64 / scale));
+            let sample = if n <= INFO_EXACT_CITIES { String::new() } else { format!(" (amostra de {} pares)", pairs.len()) };
+            println!("Distancias: min {}, media {:.2}, max {}{}", min, mean, max, sample);
+            if !instance.matrix.is_empty() {
+                let (min, mean, max) = spread(instance.matrix.iter().flatten().map(|&penalty| penalty as f64 / scale));
+                println!("Multas: min {}, media {:.2}, max {}", min, mean, max);
+            }
+        },
+        Problem::MovingTarget(instance) => {
+            println!("Tipo: TSP com alvos moveis");
+            println!("Alvos: {}", instance.targets.len());
+            println!("Velocidade do agente: {}", instance.agent_speed);
+            let speeds: Vec<f64> = instance.targets.iter().map(|t| (t.x_speed * t.x_speed + t.y_speed * t.y_speed).sqrt()).collect();
+            let (min, mean, max) = spread(speeds.iter().cloned());
+            println!("Velocidade dos alvos: min {:.3}, media {:.3}, max {:.3}", min, mean, max);
+            // The agent never intercepts a target at least as fast as itself
+            let unreachable = speeds.iter().filter(|&&speed| speed >= instance.agent_speed).count();
+            println!("Alvos tao rapidos quanto o agente: {}", unreachable);
+        },
+    }
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(value) = take_option(&mut args, "--threads") {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(value.parse().expect("Numero de threads invalido"))
+            .build_global()
+            .expect("Falha ao criar o pool de threads");
+    }
+    if args.len() < 2 {
+        println!("{}", USAGE);
+        return;
+    }
+    // Os argumentos de cada comando comecam em args[1]
+    let command = args.remove(1);
+    match command.as_str() {
+        "solve" => solve(args),
+        "bench" => bench(args),
+        "generate" => generate(args),
+        "verify" => verify(args),
+        "convert" => convert(args),
+        "info" => info(args),
+        _ => println!("{}", USAGE),
+    }
+}
@@ -0,0 +1,699 @@
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::seq::{index, SliceRandom};
+use rayon::prelude::*;
+use crate::{Cost, Instance};
+use crate::matching;
+
+#[derive(Clone, Copy, PartialEq)]
+enum InsertionRule {
+    Nearest,
+    Farthest,
+}
+
+const HILBERT_ORDER: u32 = 16;
+
+// Position of cell (x, y) along the Hilbert curve filling a 2^order x 2^order grid
+fn hilbert_index(order: u32, mut x: u64, mut y: u64) -> u64 {
+    let n: u64 = 1 << order;
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = if x & s > 0 { 1 } else { 0 };
+        let ry = if y & s > 0 { 1 } else { 0 };
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        if self.rank[ra] < self.rank[rb] {
+            self.parent[ra] = rb;
+        } else if self.rank[ra] > self.rank[rb] {
+            self.parent[rb] = ra;
+        } else {
+            self.parent[rb] = ra;
+            self.rank[ra] += 1;
+        }
+        true
+    }
+}
+
+impl Instance {
+    pub fn sequential(&self) -> Vec<usize> {
+        (0..self.size()).collect()
+    }
+
+    pub fn greedy(&self) -> Vec<usize> {
+        self.greedy_from(0)
+    }
+
+    pub fn greedy_from(&self, start: usize) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut solution = vec![start];
+        let mut visited = vec![false; number_cities];
+        visited[start] = true;
+        let mut current = start;
+        let mut min_distance = Cost::MAX;
+        let mut current_distance: Cost;
+        let mut next_city: usize = 0;
+        for _i in 0..(number_cities-1) {
+            for j in (0..number_cities).filter(|&j| !visited[j]) {
+                current_distance = self.distance(current, j);
+                if current_distance < min_distance {
+                    min_distance = current_distance;
+                    next_city = j;
+                }
+            }
+            visited[next_city] = true;
+            solution.push(next_city);
+            current = next_city;
+            min_distance = Cost::MAX;
+        }
+        solution
+    }
+
+    pub fn greedy_2_way(&self) -> Vec<usize> {
+        self.greedy_2_way_from(0)
+    }
+
+    pub fn greedy_2_way_from(&self, start: usize) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut solution_front = vec![start];
+        let mut solution_back: Vec<usize> = vec![];
+        let mut visited = vec![false; number_cities];
+        visited[start] = true;
+        let mut current = start;
+        let mut current_back = start;
+        let mut min_distance = Cost::MAX;
+        let mut current_distance: Cost;
+        let mut next_city: usize = 0;
+        let mut pushed_in_front: bool = true;
+        for _ in 0..(number_cities-1) {
+            for j in (0..number_cities).filter(|&j| !visited[j]) {
+                current_distance = self.distance(current, j);
+                if current_distance < min_distance {
+                    min_distance = current_distance;
+                    next_city = j;
+                    pushed_in_front = true;
+                }
+            }
+            for j in (0..number_cities).filter(|&j| !visited[j]) {
+                if current == current_back  {break}
+                current_distance = self.distance(j, current_back);
+                if current_distance < min_distance {
+                    min_distance = current_distance;
+                    next_city = j;
+                    pushed_in_front = false;
+                }
+            }
+            if pushed_in_front {
+                current = next_city;
+                solution_front.push(next_city);
+            } else {
+                current_back = next_city;
+                solution_back.push(next_city);
+            }
+            visited[next_city] = true;
+            min_distance = Cost::MAX;
+        }
+        let mut solution = vec![];
+        for &city in solution_back.iter().rev() {
+            solution.push(city);
+        }
+        for city in solution_front {
+            solution.push(city);
+        }
+        solution
+    }
+
+
+    // Successor array of a cyclic tour into a tour starting at city 0
+    fn tour_from_next(&self, next: &[usize]) -> Vec<usize> {
+        let mut solution = vec![0];
+        let mut current = next[0];
+        while current != 0 {
+            solution.push(current);
+            current = next[current];
+        }
+        solution
+    }
+
+    // Edge (a, next[a]) where inserting `city` costs least
+    fn cheapest_edge(&self, next: &[usize], in_tour: &[usize], city: usize) -> (usize, Cost) {
+        let mut best_edge = in_tour[0];
+        let mut best_cost = Cost::MAX;
+        for &a in in_tour {
+            let b = next[a];
+            let cost = self.distance(a, city) + self.distance(city, b) - self.distance(a, b);
+            if cost < best_cost {
+                best_cost = cost;
+                best_edge = a;
+            }
+        }
+        (best_edge, best_cost)
+    }
+
+    // Nearest and farthest insertion: the next city is the one closest to (farthest from) the
+    // partial tour, kept up to date in O(n) per step, and goes where it increases the tour least
+    fn selection_insertion(&self, rule: InsertionRule) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut next = vec![0; number_cities];
+        let mut in_tour = vec![0];
+        let mut visited = vec![false; number_cities];
+        visited[0] = true;
+        let mut distance_to_tour: Vec<Cost> = (0..number_cities).map(|c| self.distance(0, c)).collect();
+
+        for _ in 1..number_cities {
+            let mut chosen = usize::MAX;
+            for c in 0..number_cities {
+                if visited[c] {
+                    continue;
+                }
+                let better = match rule {
+                    InsertionRule::Nearest => chosen == usize::MAX || distance_to_tour[c] < distance_to_tour[chosen],
+                    InsertionRule::Farthest => chosen == usize::MAX || distance_to_tour[c] > distance_to_tour[chosen],
+                };
+                if better {
+                    chosen = c;
+                }
+            }
+
+            let (a, _) = self.cheapest_edge(&next, &in_tour, chosen);
+            next[chosen] = next[a];
+            next[a] = chosen;
+            in_tour.push(chosen);
+            visited[chosen] = true;
+            for (c, distance) in distance_to_tour.iter_mut().enumerate() {
+                *distance = (*distance).min(self.distance(chosen, c));
+            }
+        }
+        self.tour_from_next(&next)
+    }
+
+    pub fn nearest_insertion(&self) -> Vec<usize> {
+        self.selection_insertion(InsertionRule::Nearest)
+    }
+
+    pub fn farthest_insertion(&self) -> Vec<usize> {
+        self.selection_insertion(InsertionRule::Farthest)
+    }
+
+    // Cities are taken in random order, each inserted at its cheapest position
+    pub fn random_insertion(&self, rng: &mut StdRng) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut order: Vec<usize> = (1..number_cities).collect();
+        order.shuffle(rng);
+        let mut next = vec![0; number_cities];
+        let mut in_tour = vec![0];
+        for city in order {
+            let (a, _) = self.cheapest_edge(&next, &in_tour, city);
+            next[city] = next[a];
+            next[a] = city;
+            in_tour.push(city);
+        }
+        self.tour_from_next(&next)
+    }
+
+    // Inserts, at each step, the (city, edge) pair of least cost. Each city keeps its best edge;
+    // after an insertion only the two new edges are checked, and the full O(n) scan is
+    // repeated just for the cities whose best edge was the one removed
+    pub fn cheapest_insertion(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut next = vec![0; number_cities];
+        let mut in_tour = vec![0];
+        let mut visited = vec![false; number_cities];
+        visited[0] = true;
+        let mut best_edge = vec![0; number_cities];
+        let mut best_cost: Vec<Cost> = (0..number_cities).map(|c| self.distance(0, c) + self.distance(c, 0)).collect();
+
+        for _ in 1..number_cities {
+            let mut chosen = usize::MAX;
+            for c in 0..number_cities {
+                if !visited[c] && (chosen == usize::MAX || best_cost[c] < best_cost[chosen]) {
+                    chosen = c;
+                }
+            }
+
+            let a = best_edge[chosen];
+            let b = next[a];
+            next[chosen] = b;
+            next[a] = chosen;
+            in_tour.push(chosen);
+            visited[chosen] = true;
+
+            for c in 0..number_cities {
+                if visited[c] {
+                    continue;
+                }
+                if best_edge[c] == a {
+                    let (edge, cost) = self.cheapest_edge(&next, &in_tour, c);
+                    best_edge[c] = edge;
+                    best_cost[c] = cost;
+                    continue;
+                }
+                let cost_a = self.distance(a, c) + self.distance(c, chosen) - self.distance(a, chosen);
+                if cost_a < best_cost[c] {
+                    best_edge[c] = a;
+                    best_cost[c] = cost_a;
+                }
+                let cost_b = self.distance(chosen, c) + self.distance(c, b) - self.distance(chosen, b);
+                if cost_b < best_cost[c] {
+                    best_edge[c] = chosen;
+                    best_cost[c] = cost_b;
+                }
+            }
+        }
+        self.tour_from_next(&next)
+    }
+
+    // Walks a set of edges forming a single Hamiltonian path over `cities` (each with degree <= 2)
+    fn path_from_edges(&self, adjacency: &[Vec<usize>], cities: &[usize]) -> Vec<usize> {
+        let start = match cities.iter().find(|&&c| adjacency[c].len() < 2) {
+            Some(&c) => c,
+            None => return cities.to_vec(),
+        };
+        let mut path = vec![start];
+        let mut previous = usize::MAX;
+        let mut current = start;
+        while let Some(&next) = adjacency[current].iter().find(|&&c| c != previous) {
+            previous = current;
+            current = next;
+            path.push(current);
+        }
+        path
+    }
+
+    // Adds `edges` in the given order whenever both ends still have degree < 2 and the edge
+    // does not close a cycle, until the chosen cities form a single path
+    fn join_edges(&self, edges: &[(usize, usize)], cities: &[usize]) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut adjacency: Vec<Vec<usize>> = vec![vec![]; number_cities];
+        let mut components = UnionFind::new(number_cities);
+        let mut added = 0;
+        for &(i, j) in edges {
+            if added + 1 >= cities.len() {
+                break;
+            }
+            if adjacency[i].len() < 2 && adjacency[j].len() < 2 && components.union(i, j) {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+                added += 1;
+            }
+        }
+        self.path_from_edges(&adjacency, cities)
+    }
+
+    // Greedy edge matching: the shortest remaining edge is added while it keeps every degree
+    // <= 2 and closes no subtour; the last edge joins the ends of the resulting path
+    pub fn greedy_edge(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut edges: Vec<(usize, usize)> = vec![];
+        for i in 0..number_cities {
+            for j in i+1..number_cities {
+                edges.push((i, j));
+            }
+        }
+        edges.sort_by_key(|&(i, j)| self.distance(i, j));
+        self.join_edges(&edges, &(0..number_cities).collect::<Vec<usize>>())
+    }
+
+    // Clarke-Wright savings: every city starts in its own route hub-i-hub and routes are merged
+    // by their ends in decreasing order of saving d(hub,i) + d(hub,j) - d(i,j)
+    pub fn savings(&self, hub: usize) -> Vec<usize> {
+        let number_cities = self.size();
+        let others: Vec<usize> = (0..number_cities).filter(|&c| c != hub).collect();
+        let mut edges: Vec<(usize, usize)> = vec![];
+        for a in 0..others.len() {
+            for b in a+1..others.len() {
+                edges.push((others[a], others[b]));
+            }
+        }
+        edges.sort_by_key(|&(i, j)| {
+            -(self.distance(hub, i) + self.distance(hub, j) - self.distance(i, j))
+        });
+        let mut solution = vec![hub];
+        solution.extend(self.join_edges(&edges, &others));
+        solution
+    }
+
+    // Prim's algorithm in O(n^2); parent[root] = root
+    pub fn minimum_spanning_tree(&self, root: usize) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut parent = vec![root; number_cities];
+        let mut in_tree = vec![false; number_cities];
+        let mut key: Vec<Cost> = (0..number_cities).map(|c| self.distance(root, c)).collect();
+        in_tree[root] = true;
+        for _ in 1..number_cities {
+            let mut chosen = usize::MAX;
+            for c in 0..number_cities {
+                if !in_tree[c] && (chosen == usize::MAX || key[c] < key[chosen]) {
+                    chosen = c;
+                }
+            }
+            in_tree[chosen] = true;
+            for c in 0..number_cities {
+                if !in_tree[c] && self.distance(chosen, c) < key[c] {
+                    key[c] = self.distance(chosen, c);
+                    parent[c] = chosen;
+                }
+            }
+        }
+        parent
+    }
+
+    // Double-tree 2-approximation: the preorder walk of the MST is the shortcut of the
+    // Euler tour of the tree with every edge doubled
+    pub fn double_mst(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        let parent = self.minimum_spanning_tree(0);
+        let mut children: Vec<Vec<usize>> = vec![vec![]; number_cities];
+        for c in 1..number_cities {
+            children[parent[c]].push(c);
+        }
+        let mut solution = vec![];
+        let mut stack = vec![0];
+        while let Some(c) = stack.pop() {
+            solution.push(c);
+            for &child in children[c].iter().rev() {
+                stack.push(child);
+            }
+        }
+        solution
+    }
+
+    // Euler circuit (Hierholzer) of a connected multigraph with all degrees even,
+    // shortcut to visit each city once
+    fn shortcut_euler_tour(&self, adjacency: &mut [Vec<usize>]) -> Vec<usize> {
+        let mut visited = vec![false; adjacency.len()];
+        let mut solution = vec![];
+        let mut stack = vec![0];
+        while let Some(&c) = stack.last() {
+            match adjacency[c].pop() {
+                Some(next) => {
+                    let back = adjacency[next].iter().position(|&x| x == c).unwrap();
+                    adjacency[next].swap_remove(back);
+                    stack.push(next);
+                },
+                None => {
+                    stack.pop();
+                    if !visited[c] {
+                        visited[c] = true;
+                        solution.push(c);
+                    }
+                },
+            }
+        }
+        solution.reverse();
+        solution
+    }
+
+    // Christofides 1.5-approximation: MST plus a minimum cost perfect matching on the
+    // odd-degree cities, whose Euler tour is shortcut into a tour
+    pub fn christofides(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        if number_cities < 3 {
+            return self.sequential();
+        }
+        let parent = self.minimum_spanning_tree(0);
+        let mut adjacency: Vec<Vec<usize>> = vec![vec![]; number_cities];
+        for c in 1..number_cities {
+            adjacency[c].push(parent[c]);
+            adjacency[parent[c]].push(c);
+        }
+
+        let odd: Vec<usize> = (0..number_cities).filter(|&c| adjacency[c].len() % 2 == 1).collect();
+        let costs: Vec<Vec<i64>> = odd.iter()
+            .map(|&a| odd.iter().map(|&b| self.distance(a, b)).collect())
+            .collect();
+        let mates = matching::minimum_perfect_matching(&costs);
+        for (i, &j) in mates.iter().enumerate() {
+            if i < j {
+                adjacency[odd[i]].push(odd[j]);
+                adjacency[odd[j]].push(odd[i]);
+            }
+        }
+        self.shortcut_euler_tour(&mut adjacency)
+    }
+
+    // Visits the cities in the order of a Hilbert curve over their bounding box. Uses only the
+    // coordinates and a sort, O(n log n), so it also works when the distances were not computed.
+    // Instances without coordinates get an empty tour
+    pub fn hilbert_curve(&self) -> Vec<usize> {
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for city in &self.cities {
+            min_x = min_x.min(city.x);
+            min_y = min_y.min(city.y);
+            max_x = max_x.max(city.x);
+            max_y = max_y.max(city.y);
+        }
+        let side = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+        let cells = ((1u64 << HILBERT_ORDER) - 1) as f64;
+
+        let mut keyed: Vec<(u64, usize)> = self.cities.iter().enumerate().map(|(i, city)| {
+            let x = ((city.x - min_x) / side * cells) as u64;
+            let y = ((city.y - min_y) / side * cells) as u64;
+            (hilbert_index(HILBERT_ORDER, x, y), i)
+        }).collect();
+        keyed.sort_unstable();
+        keyed.into_iter().map(|(_, i)| i).collect()
+    }
+
+    fn penalty(&self, city: usize, position: usize) -> Cost {
+        if self.matrix.is_empty() { 0 } else { self.matrix[city][position] }
+    }
+
+    // Cost of putting `city` at `position` right after `previous` (None at position 0),
+    // including the closing edge when it is the last position
+    fn position_cost(&self, previous: Option<usize>, first: usize, city: usize, position: usize) -> Cost {
+        let mut cost = self.penalty(city, position);
+        if let Some(p) = previous {
+            cost += self.distance(p, city);
+        }
+        if position == self.size() - 1 {
+            cost += self.distance(city, first);
+        }
+        cost
+    }
+
+    // Nearest neighbor under the positional objective: at position k the next city is the one
+    // minimizing distance from the previous city plus matrix[city][k]
+    pub fn penalty_greedy(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        let mut visited = vec![false; number_cities];
+        let mut solution: Vec<usize> = vec![];
+        for k in 0..number_cities {
+            let previous = solution.last().cloned();
+            let first = solution.first().cloned().unwrap_or(0);
+            let mut chosen = usize::MAX;
+            let mut best_cost = Cost::MAX;
+            for c in (0..number_cities).filter(|&c| !visited[c]) {
+                let cost = self.position_cost(previous, first, c, k);
+                if cost < best_cost {
+                    best_cost = cost;
+                    chosen = c;
+                }
+            }
+            visited[chosen] = true;
+            solution.push(chosen);
+        }
+        solution
+    }
+
+    // Regret variant: every city compares its cost at position k with its best option later on
+    // (cheapest remaining position plus its nearest neighbor distance). The city that loses most
+    // by waiting, i.e. whose cheap positions are about to pass, is placed now
+    pub fn penalty_regret(&self) -> Vec<usize> {
+        let number_cities = self.size();
+        if number_cities < 2 {
+            return self.sequential();
+        }
+        let mut best_later = vec![vec![Cost::MAX; number_cities + 1]; number_cities];
+        for (c, later) in best_later.iter_mut().enumerate() {
+            for k in (0..number_cities).rev() {
+                later[k] = later[k+1].min(self.penalty(c, k));
+            }
+        }
+        let nearest: Vec<Cost> = (0..number_cities)
+            .map(|c| (0..number_cities).filter(|&o| o != c).map(|o| self.distance(c, o)).min().unwrap())
+            .collect();
+
+        let mut visited = vec![false; number_cities];
+        let mut solution: Vec<usize> = vec![];
+        for k in 0..number_cities {
+            let previous = solution.last().cloned();
+            let first = solution.first().cloned().unwrap_or(0);
+            let mut chosen = usize::MAX;
+            let mut best_regret = Cost::MIN;
+            let mut chosen_cost = Cost::MAX;
+            for c in 0..number_cities {
+                if visited[c] {
+                    continue;
+                }
+                let now = self.position_cost(previous, first, c, k);
+                let regret = if k + 1 < number_cities {
+                    best_later[c][k+1] + nearest[c] - now
+                } else {
+                    0
+                };
+                if regret > best_regret || (regret == best_regret && now < chosen_cost) {
+                    best_regret = regret;
+                    chosen_cost = now;
+                    chosen = c;
+                }
+            }
+            visited[chosen] = true;
+            solution.push(chosen);
+        }
+        solution
+    }
+
+    // The same cycle written from city 0, and on symmetric instances in the direction whose
+    // second city is smaller, so that rotations and reversals of a tour compare equal
+    fn canonical_cycle(&self, tour: &[usize]) -> Vec<usize> {
+        let n = tour.len();
+        let zero = tour.iter().position(|&city| city == 0).unwrap_or(0);
+        let forward: Vec<usize> = (0..n).map(|k| tour[(zero + k) % n]).collect();
+        let backward: Vec<usize> = (0..n).map(|k| tour[(zero + n - k) % n]).collect();
+        if self.symmetric && backward < forward { backward } else { forward }
+    }
+
+    // Nearest neighbor and two-way greedy from every start city (or `sample` random ones), each
+    // tour also taken in the opposite direction, in parallel. Returns the `k` best distinct
+    // cycles; ties are broken by start city and variant so the result does not depend on
+    // scheduling
+    pub fn multi_start_greedy(&self, sample: Option<usize>, k: usize, rng: &mut StdRng) -> Vec<Vec<usize>> {
+        let number_cities = self.size();
+        let starts: Vec<usize> = match sample {
+            Some(m) if m < number_cities => {
+                let mut chosen = index::sample(rng, number_cities, m).into_vec();
+                chosen.sort();
+                chosen
+            },
+            _ => (0..number_cities).collect(),
+        };
+
+        let mut candidates: Vec<(Cost, usize, usize, Vec<usize>)> = starts.par_iter().flat_map_iter(|&start| {
+            let tours = [self.greedy_from(start), self.greedy_2_way_from(start)];
+            let mut variants = vec![];
+            for (v, tour) in tours.into_iter().enumerate() {
+                let mut reversed = tour.clone();
+                reversed[1..].reverse();
+                for (d, candidate) in [tour, reversed].into_iter().enumerate() {
+                    let eval = self.evaluate(&candidate);
+                    variants.push((eval, start, 2 * v + d, candidate));
+                }
+            }
+            variants
+        }).collect();
+        candidates.sort_by_key(|a| (a.0, a.1, a.2));
+        let mut seen = HashSet::new();
+        candidates.into_iter()
+            .filter(|c| seen.insert(self.canonical_cycle(&c.3)))
+            .take(k)
+            .map(|c| c.3)
+            .collect()
+    }
+
+    // On asymmetric instances the constructors that work on undirected edges (greedy edge,
+    // savings, MST-based, Hilbert) may return the tour in its worse direction, so both are tried
+    pub fn construct(&self, method: &str, rng: &mut StdRng) -> Vec<usize> {
+        let mut solution = self.construct_directed(method, rng);
+        if !self.symmetric {
+            let reversed: Vec<usize> = solution.iter().rev().cloned().collect();
+            if self.evaluate(&reversed) < self.evaluate(&solution) {
+                solution = reversed;
+            }
+        }
+        solution
+    }
+
+    fn construct_directed(&self, method: &str, rng: &mut StdRng) -> Vec<usize> {
+        match method {
+            "random" => {
+                let mut solution = self.sequential();
+                solution.shuffle(rng);
+                solution
+            },
+            "sequential" => self.sequential(),
+            "greedy" => self.greedy(),
+            "greedy-2-way" => self.greedy_2_way(),
+            "nearest-insertion" => self.nearest_insertion(),
+            "farthest-insertion" => self.farthest_insertion(),
+            "cheapest-insertion" => self.cheapest_insertion(),
+            "random-insertion" => self.random_insertion(rng),
+            "greedy-edge" => self.greedy_edge(),
+            "savings" => self.savings(0),
+            "double-mst" => self.double_mst(),
+            "christofides" => self.christofides(),
+            "hilbert" if self.cities.len() == self.size() => self.hilbert_curve(),
+            // Explicit matrices (and ATSP) have no coordinates: nearest neighbor instead
+            "hilbert" => self.greedy(),
+            "penalty-greedy" => self.penalty_greedy(),
+            "penalty-regret" => self.penalty_regret(),
+            "multi-start-greedy" => self.multi_start_greedy(None, 1, rng).remove(0),
+            _ => panic!("Construtor desconhecido: {}", method),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use crate::City;
+
+    #[test]
+    fn multi_start_greedy_returns_distinct_cycles() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut instance = Instance::new();
+        instance.cities = (0..60).map(|_| City { x: rng.gen_range(0.0..1000.0), y: rng.gen_range(0.0..1000.0) }).collect();
+        instance.compute_distances();
+
+        let tours = instance.multi_start_greedy(None, 8, &mut rng);
+        assert_eq!(tours.len(), 8);
+        let cycles: HashSet<Vec<usize>> = tours.iter().map(|tour| instance.canonical_cycle(tour)).collect();
+        assert_eq!(cycles.len(), tours.len());
+    }
+}
@@ -0,0 +1,189 @@
+use rayon::prelude::*;
+use crate::{Cost, Instance};
+
+// Largest distance matrix kept in memory, in bytes
+const MATRIX_MEMORY: usize = 1 << 29;
+// Nearest neighbors whose distances are cached for each city when computing on the fly
+const NEIGHBOR_CACHE_SIZE: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Storage {
+    Flat,
+    Triangular,
+    OnTheFly,
+}
+
+pub enum Distances {
+    // Row-major n x n matrix in a single allocation
+    Flat { n: usize, data: Vec<Cost> },
+    // Strict lower triangle of a symmetric matrix: (i, j) with i > j at i (i - 1) / 2 + j
+    Triangular { n: usize, data: Vec<Cost> },
+    // Computed from the coordinates on each access, except to the (approximately, for
+    // geographic metrics) nearest neighbors of each city, whose distances are cached
+    OnTheFly { n: usize, neighbors: Vec<Vec<(usize, Cost)>> },
+}
+
+impl Distances {
+    pub fn empty() -> Self {
+        Distances::Flat { n: 0, data: vec![] }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Distances::Flat { n, .. } | Distances::Triangular { n, .. } | Distances::OnTheFly { n, .. } => *n,
+        }
+    }
+}
+
+fn triangle_index(i: usize, j: usize) -> usize {
+    i * (i - 1) / 2 + j
+}
+
+impl Instance {
+    // The flat matrix, used by every instance that fits in memory, is read inline; the other
+    // backends pay for a call
+    #[inline]
+    pub fn distance(&self, i: usize, j: usize) -> Cost {
+        match &self.distances {
+            Distances::Flat { n, data } => data[i * n + j],
+            _ => self.large_distance(i, j),
+        }
+    }
+
+    #[inline(never)]
+    fn large_distance(&self, i: usize, j: usize) -> Cost {
+        match &self.distances {
+            Distances::Flat { n, data } => data[i * n + j],
+            Distances::Triangular { .. } | Distances::OnTheFly { .. } if i == j => 0,
+            Distances::Triangular { data, .. } => data[triangle_index(i.max(j), i.min(j))],
+            Distances::OnTheFly { neighbors, .. } => match neighbors[i].iter().find(|&&(c, _)| c == j) {
+                Some(&(_, cost)) => cost,
+                None => self.metric.cost(&self.cities[i], &self.cities[j], self.scale),
+            },
+        }
+    }
+
+    pub fn storage(&self) -> Storage {
+        match &self.distances {
+            Distances::Flat { .. } => Storage::Flat,
+            Distances::Triangular { .. } => Storage::Triangular,
+            Distances::OnTheFly { .. } => Storage::OnTheFly,
+        }
+    }
+
+    // The full matrix while it fits in MATRIX_MEMORY, then half of it, then no matrix at all
+    pub fn storage_for(n: usize) -> Storage {
+        let bytes = std::mem::size_of::<Cost>();
+        if n * n * bytes <= MATRIX_MEMORY {
+            Storage::Flat
+        } else if n * (n - 1) / 2 * bytes <= MATRIX_MEMORY {
+            Storage::Triangular
+        } else {
+            Storage::OnTheFly
+        }
+    }
+
+    pub fn compute_distances(&mut self) {
+        self.compute_distances_as(Instance::storage_for(self.cities.len()));
+    }
+
+    pub fn compute_distances_as(&mut self, storage: Storage) {
+        let n = self.cities.len();
+        let cost = |i: usize, j: usize| self.metric.cost(&self.cities[i], &self.cities[j], self.scale);
+        self.distances = match storage {
+            Storage::Flat => {
+                let mut data = vec![0; n * n];
+                data.par_chunks_mut(n.max(1)).enumerate().for_each(|(i, row)| {
+                    for (j, value) in row.iter_mut().enumerate() {
+                        // GEO costs at least 1 even between equal coordinates
+                        *value = if i == j { 0 } else { cost(i, j) };
+                    }
+                });
+                Distances::Flat { n, data }
+            },
+            Storage::Triangular => {
+                let data = (1..n).into_par_iter()
+                    .flat_map_iter(|i| (0..i).map(move |j| (i, j)))
+                    .map(|(i, j)| cost(i, j))
+                    .collect();
+                Distances::Triangular { n, data }
+            },
+            Storage::OnTheFly => {
+                let neighbors = self.nearest_neighbors(NEIGHBOR_CACHE_SIZE).into_par_iter()
+                    .enumerate()
+                    .map(|(i, near)| near.into_iter().map(|j| (j, cost(i, j))).collect())
+                    .collect();
+                Distances::OnTheFly { n, neighbors }
+            },
+        };
+        self.symmetric = true;
+    }
+
+    // Explicit matrices (ATSP, user supplied) have no coordinates, so they are always stored;
+    // symmetric ones in half the memory when the full matrix would not fit
+    pub fn set_distance_matrix(&mut self, rows: Vec<Vec<Cost>>) {
+        let n = rows.len();
+        self.symmetric = (0..n).all(|i| (0..i).all(|j| rows[i][j] == rows[j][i]));
+        self.distances = if self.symmetric && Instance::storage_for(n) != Storage::Flat {
+            let data = (1..n).flat_map(|i| rows[i][..i].to_vec()).collect();
+            Distances::Triangular { n, data }
+        } else {
+            Distances::Flat { n, data: rows.into_iter().flatten().collect() }
+        };
+    }
+
+    // k nearest cities of each city by planar distance between the coordinates, found by
+    // searching rings of cells of a uniform grid around the city
+    fn nearest_neighbors(&self, k: usize) -> Vec<Vec<usize>> {
+        let n = self.cities.len();
+        let k = k.min(n.saturating_sub(1));
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for city in &self.cities {
+            min_x = min_x.min(city.x);
+            min_y = min_y.min(city.y);
+            max_x = max_x.max(city.x);
+            max_y = max_y.max(city.y);
+        }
+        // About two cities per cell
+        let side = ((n / 2).max(1) as f64).sqrt().ceil() as usize;
+        let cell_size = ((max_x - min_x).max(max_y - min_y) / side as f64).max(f64::EPSILON);
+        let cell_of = |value: f64, min: f64| (((value - min) / cell_size) as usize).min(side - 1);
+        let mut cells: Vec<Vec<usize>> = vec![vec![]; side * side];
+        for (i, city) in self.cities.iter().enumerate() {
+            cells[cell_of(city.y, min_y) * side + cell_of(city.x, min_x)].push(i);
+        }
+
+        (0..n).into_par_iter().map(|i| {
+            let city = &self.cities[i];
+            let (cx, cy) = (cell_of(city.x, min_x) as i64, cell_of(city.y, min_y) as i64);
+            let mut found: Vec<(f64, usize)> = vec![];
+            for ring in 0..=side as i64 {
+                // Cities not yet seen lie in this ring or farther, at least `(ring - 1) * cell_size` away
+                if ring > 0 && found.len() >= k {
+                    let reach = (ring - 1) as f64 * cell_size;
+                    if found[k-1].0 <= reach * reach {
+                        break;
+                    }
+                }
+                for y in cy - ring..=cy + ring {
+                    for x in cx - ring..=cx + ring {
+                        let on_ring = (y - cy).abs() == ring || (x - cx).abs() == ring;
+                        if !on_ring || x < 0 || y < 0 || x >= side as i64 || y >= side as i64 {
+                            continue;
+                        }
+                        for &j in &cells[y as usize * side + x as usize] {
+                            if j != i {
+                                let (dx, dy) = (self.cities[j].x - city.x, self.cities[j].y - city.y);
+                                found.push((dx * dx + dy * dy, j));
+                            }
+                        }
+                    }
+                }
+                found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                found.truncate(k);
+            }
+            found.into_iter().map(|(_, j)| j).collect()
+        }).collect()
+    }
+}
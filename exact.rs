@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use crate::{Cost, Instance};
+use crate::bounds::assignment;
+
+// Largest instance solved by dynamic programming: 2^(n-1) * (n-1) states for each start city,
+// kept in a single table of 8-byte costs (80 MB at n = 20) reused by the start cities
+pub const DP_MAX_CITIES: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Proof {
+    // Every (visited set, last city) state was solved
+    DynamicProgramming { states: u64 },
+    // The search tree was exhausted, every pruned node having a lower bound not below the
+    // optimum; `root_bound` is the bound before branching
+    BranchAndBound { nodes: u64, root_bound: Cost },
+}
+
+pub struct ExactSolution {
+    pub tour: Vec<usize>,
+    pub value: Cost,
+    pub proof: Proof,
+}
+
+struct Search<'a> {
+    instance: &'a Instance,
+    // Held-Karp potentials from the root, used to tighten the spanning tree bounds
+    potentials: Vec<f64>,
+    best_tour: Vec<usize>,
+    best_value: Cost,
+    nodes: u64,
+}
+
+impl Instance {
+    // How the optimality of a tour was proven, as printed by the programs
+    pub fn format_proof(&self, proof: &Proof) -> String {
+        match proof {
+            Proof::DynamicProgramming { states } =>
+                format!("Otimo provado por programacao dinamica ({} estados)", states),
+            Proof::BranchAndBound { nodes, root_bound } =>
+                format!("Otimo provado por branch-and-bound ({} nos, limite na raiz {})", nodes, self.format_cost(*root_bound)),
+        }
+    }
+
+    fn penalty_at(&self, city: usize, position: usize) -> Cost {
+        if self.matrix.is_empty() { 0 } else { self.matrix[city][position] }
+    }
+
+    // Dynamic programming solver when it fits, branch-and-bound from `incumbent` otherwise
+    pub fn exact(&self, incumbent: &[usize]) -> ExactSolution {
+        if self.size() <= DP_MAX_CITIES {
+            self.held_karp_dp()
+        } else {
+            self.branch_and_bound(incumbent)
+        }
+    }
+
+    // Held-Karp (Bellman) dynamic programming. The position of a city is the number of cities
+    // visited before it, so the penalties fit in the same states. Without penalties the tour
+    // can start at city 0; with them every start city is solved, one after the other so that
+    // only one table is alive
+    pub fn held_karp_dp(&self) -> ExactSolution {
+        let n = self.size();
+        assert!(n <= DP_MAX_CITIES, "Instancia grande demais para a programacao dinamica");
+        if n < 3 {
+            let tour = self.best_rotation(&self.sequential());
+            let value = self.evaluate(&tour);
+            return ExactSolution { tour, value, proof: Proof::DynamicProgramming { states: 0 } };
+        }
+        let starts: Vec<usize> = if self.matrix.is_empty() { vec![0] } else { (0..n).collect() };
+        let mut table = vec![];
+        let (value, tour) = starts.iter()
+            .map(|&start| self.dp_from(start, &mut table))
+            .min_by_key(|(value, _)| *value)
+            .unwrap();
+        let states = starts.len() as u64 * (1u64 << (n - 1)) * (n as u64 - 1);
+        ExactSolution { tour, value, proof: Proof::DynamicProgramming { states } }
+    }
+
+    fn dp_from(&self, start: usize, cost: &mut Vec<Cost>) -> (Cost, Vec<usize>) {
+        let n = self.size();
+        let others: Vec<usize> = (0..n).filter(|&c| c != start).collect();
+        let m = others.len();
+        let full = (1usize << m) - 1;
+        cost.clear();
+        cost.resize((full + 1) * m, Cost::MAX);
+
+        for j in 0..m {
+            cost[(1 << j) * m + j] = self.penalty_at(start, 0)
+                + self.distance(start, others[j])
+                + self.penalty_at(others[j], 1);
+        }
+        for mask in 1..=full {
+            let position = mask.count_ones() as usize + 1;
+            for j in 0..m {
+                let current = cost[mask * m + j];
+                if current == Cost::MAX || mask & (1 << j) == 0 {
+                    continue;
+                }
+                for k in 0..m {
+                    if mask & (1 << k) == 0 {
+                        let next = (mask | (1 << k)) * m + k;
+                        let candidate = current
+                            + self.distance(others[j], others[k])
+                            + self.penalty_at(others[k], position);
+                        if candidate < cost[next] {
+                            cost[next] = candidate;
+                        }
+                    }
+                }
+            }
+        }
+
+        let (value, mut last) = (0..m)
+            .map(|j| (cost[full * m + j] + self.distance(others[j], start), j))
+            .min()
+            .unwrap();
+
+        // Walks the states back, finding at each step a predecessor consistent with the cost
+        let mut tour = vec![others[last]];
+        let mut mask = full;
+        while mask.count_ones() > 1 {
+            let previous = mask & !(1 << last);
+            let position = mask.count_ones() as usize;
+            let target = cost[mask * m + last];
+            last = (0..m)
+                .find(|&j| {
+                    previous & (1 << j) != 0 && cost[previous * m + j] != Cost::MAX
+                        && cost[previous * m + j] + self.distance(others[j], others[last])
+                            + self.penalty_at(others[last], position) == target
+                })
+                .unwrap();
+            tour.push(others[last]);
+            mask = previous;
+        }
+        tour.push(start);
+        tour.reverse();
+        (value, tour)
+    }
+
+    // Depth-first branch-and-bound that fixes the tour one position at a time. The bound of a
+    // partial path adds a spanning tree bound on the path that closes it through the remaining
+    // cities and an optimal assignment of the remaining cities to the remaining positions
+    pub fn branch_and_bound(&self, incumbent: &[usize]) -> ExactSolution {
+        let n = self.size();
+        let best_tour = self.best_rotation(incumbent);
+        let best_value = self.evaluate(&best_tour);
+
+        let root = self.held_karp_bound(best_value, 10 * n);
+        let mut root_bound = root.value;
+        if !self.matrix.is_empty() {
+            root_bound += assignment(&self.matrix).0;
+        }
+
+        let mut search = Search { instance: self, potentials: root.potentials, best_tour, best_value, nodes: 0 };
+        if root_bound < search.best_value {
+            let starts: Vec<usize> = if self.matrix.is_empty() { vec![0] } else { (0..n).collect() };
+            let mut visited = vec![false; n];
+            for start in starts {
+                visited[start] = true;
+                search.branch(&mut vec![start], &mut visited, self.penalty_at(start, 0));
+                visited[start] = false;
+            }
+        }
+
+        ExactSolution {
+            tour: search.best_tour,
+            value: search.best_value,
+            proof: Proof::BranchAndBound { nodes: search.nodes, root_bound },
+        }
+    }
+}
+
+impl<'a> Search<'a> {
+    fn branch(&mut self, path: &mut Vec<usize>, visited: &mut Vec<bool>, cost: Cost) {
+        self.nodes += 1;
+        let instance = self.instance;
+        let n = instance.size();
+        let last = *path.last().unwrap();
+        if path.len() == n {
+            let total = cost + instance.distance(last, path[0]);
+            if total < self.best_value {
+                self.best_value = total;
+                self.best_tour = path.clone();
+            }
+            return;
+        }
+        if cost + self.bound(path, visited) >= self.best_value {
+            return;
+        }
+
+        let position = path.len();
+        let mut children: Vec<(Cost, usize)> = (0..n)
+            .filter(|&c| !visited[c])
+            .map(|c| (instance.distance(last, c) + instance.penalty_at(c, position), c))
+            .collect();
+        children.sort();
+        for (step, c) in children {
+            if cost + step >= self.best_value {
+                continue;
+            }
+            path.push(c);
+            visited[c] = true;
+            self.branch(path, visited, cost + step);
+            visited[c] = false;
+            path.pop();
+        }
+    }
+
+    // Lower bound on the cost still to come after `path`
+    fn bound(&self, path: &[usize], visited: &[bool]) -> Cost {
+        let instance = self.instance;
+        let pi = &self.potentials;
+        let remaining: Vec<usize> = (0..visited.len()).filter(|&c| !visited[c]).collect();
+        let (first, last) = (path[0], *path.last().unwrap());
+        let cost = |i: usize, j: usize| instance.distance(i, j) as f64 + pi[i] + pi[j];
+        let tree_cost = |i: usize, j: usize| instance.undirected_distance(i, j) as f64 + pi[i] + pi[j];
+
+        // The path last -> remaining -> first spans the remaining cities and has one edge out of
+        // each end. Under potentials, its reduced cost exceeds its cost by 2 pi for every
+        // remaining city plus pi at both ends
+        let mut tree = 0.0;
+        let mut in_tree = vec![false; remaining.len()];
+        let mut key = vec![f64::INFINITY; remaining.len()];
+        key[0] = 0.0;
+        for _ in 0..remaining.len() {
+            let mut chosen = usize::MAX;
+            for i in 0..remaining.len() {
+                if !in_tree[i] && (chosen == usize::MAX || key[i] < key[chosen]) {
+                    chosen = i;
+                }
+            }
+            in_tree[chosen] = true;
+            tree += key[chosen];
+            for i in 0..remaining.len() {
+                if !in_tree[i] {
+                    key[i] = key[i].min(tree_cost(remaining[chosen], remaining[i]));
+                }
+            }
+        }
+        let leave = remaining.iter().map(|&c| cost(last, c)).fold(f64::INFINITY, f64::min);
+        let enter = remaining.iter().map(|&c| cost(c, first)).fold(f64::INFINITY, f64::min);
+        let offset = 2.0 * remaining.iter().map(|&c| pi[c]).sum::<f64>() + pi[first] + pi[last];
+        let distance = (tree + leave + enter - offset - 1e-6).ceil() as Cost;
+
+        if instance.matrix.is_empty() {
+            return distance;
+        }
+        let position = path.len();
+        let costs: Vec<Vec<Cost>> = remaining.iter()
+            .map(|&c| (position..visited.len()).map(|k| instance.matrix[c][k]).collect())
+            .collect();
+        distance + assignment(&costs).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    // Random distances (asymmetric unless `symmetric`) and, when `penalties`, a penalty matrix
+    fn random_instance(rng: &mut StdRng, n: usize, symmetric: bool, penalties: bool) -> Instance {
+        let random: Vec<Vec<Cost>> = (0..n).map(|_| (0..n).map(|_| rng.gen_range(1..100)).collect()).collect();
+        let rows = (0..n)
+            .map(|i| (0..n).map(|j| match (i == j, symmetric) {
+                (true, _) => 0,
+                (false, true) => random[i.min(j)][i.max(j)],
+                (false, false) => random[i][j],
+            }).collect())
+            .collect();
+        let mut instance = Instance::new();
+        instance.set_distance_matrix(rows);
+        if penalties {
+            instance.matrix = (0..n).map(|_| (0..n).map(|_| rng.gen_range(0..60)).collect()).collect();
+        }
+        instance
+    }
+
+    // Best value over every permutation of `tour[k..]`
+    fn brute_force(instance: &Instance, tour: &mut Vec<usize>, k: usize) -> Cost {
+        if k == tour.len() {
+            return instance.evaluate(tour);
+        }
+        let mut best = Cost::MAX;
+        for i in k..tour.len() {
+            tour.swap(k, i);
+            best = best.min(brute_force(instance, tour, k + 1));
+            tour.swap(k, i);
+        }
+        best
+    }
+
+    fn check(solution: &ExactSolution, instance: &Instance, optimum: Cost) {
+        let mut sorted = solution.tour.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..instance.size()).collect::<Vec<usize>>());
+        assert_eq!(instance.evaluate(&solution.tour), solution.value);
+        assert_eq!(solution.value, optimum);
+    }
+
+    #[test]
+    fn exact_solvers_find_the_optimum() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for trial in 0..56 {
+            let n = 3 + trial % 7;
+            let instance = random_instance(&mut rng, n, trial % 2 == 0, trial % 4 < 2);
+            let optimum = brute_force(&instance, &mut (0..n).collect(), 0);
+
+            check(&instance.held_karp_dp(), &instance, optimum);
+            let incumbent: Vec<usize> = (0..n).collect();
+            check(&instance.branch_and_bound(&incumbent), &instance, optimum);
+        }
+    }
+}
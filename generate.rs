@@ -0,0 +1,42 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use crate::{City, Cost, Instance};
+use crate::mttsp::{self, Target};
+
+// Synthetic instances with coordinates uniform in a side x side square, rounded to one decimal
+// like the course instances
+
+fn coordinate(side: f64, rng: &mut StdRng) -> f64 {
+    (rng.gen_range(0.0..side) * 10.0).round() / 10.0
+}
+
+impl Instance {
+    pub fn random(n: usize, side: f64, rng: &mut StdRng) -> Instance {
+        let mut instance = Instance::new();
+        instance.cities = (0..n).map(|_| City { x: coordinate(side, rng), y: coordinate(side, rng) }).collect();
+        instance.compute_distances();
+        instance
+    }
+
+    // Penalty of each city at each position, uniform in 0..=max_penalty
+    pub fn random_penalties(&mut self, max_penalty: Cost, rng: &mut StdRng) {
+        let n = self.size();
+        self.matrix = (0..n).map(|_| (0..n).map(|_| rng.gen_range(0..=max_penalty) * self.scale).collect()).collect();
+    }
+}
+
+impl mttsp::Instance {
+    // Targets move in uniformly random directions with speeds up to max_target_speed, which
+    // must be below the agent speed for every target to be reachable
+    pub fn random(n: usize, side: f64, agent_speed: f64, max_target_speed: f64, rng: &mut StdRng) -> mttsp::Instance {
+        let mut instance = mttsp::Instance::new();
+        instance.agent_speed = agent_speed;
+        instance.targets = (0..n).map(|_| {
+            let (x, y) = (coordinate(side, rng), coordinate(side, rng));
+            let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+            let speed = rng.gen_range(0.0..=max_target_speed);
+            Target { x, y, x_speed: speed * angle.cos(), y_speed: speed * angle.sin() }
+        }).collect();
+        instance
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::read_file;
+use crate::distances::Distances;
+use crate::metrics::Metric;
+
+// Tour costs, distances and penalties. Fractional values are kept as fixed point in units of
+// 1/Instance::scale
+pub type Cost = i64;
+
+// Scale that keeps fractional costs to the micro-unit (--exact)
+pub const EXACT_SCALE: Cost = 1_000_000;
+
+// Rows after the header line, in units of 1/scale; missing or invalid values count as 0
+fn read_matrix(content: &str, scale: Cost) -> Vec<Vec<Cost>> {
+    content.lines().skip(1)
+        .map(|line| line.split_whitespace()
+            .map(|value| (value.parse::<f64>().unwrap_or(0.0) * scale as f64).round() as Cost)
+            .collect())
+        .filter(|row: &Vec<Cost>| !row.is_empty())
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct City {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub struct Instance {
+    // Penalty of visiting city i at position k, empty for the static TSP
+    pub matrix: Vec<Vec<Cost>>,
+    pub cities: Vec<City>,
+    // Read through distance(i, j); the storage depends on the size of the instance
+    pub(crate) distances: Distances,
+    // distance(i, j) == distance(j, i) for every pair; false for ATSP instances
+    pub symmetric: bool,
+    // Metric of the coordinates; TSPLIB files set their own
+    pub metric: Metric,
+    // Costs are in units of 1/scale: 1 rounds every distance to an integer
+    pub scale: Cost,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Instance {
+    pub fn new() -> Self {
+        Self {
+            matrix: Vec::new(),
+            cities: Vec::new(),
+            distances: Distances::empty(),
+            symmetric: true,
+            metric: Metric::Euclidean,
+            scale: 1,
+        }
+    }
+
+    pub fn set_data(&mut self, tspp_file_name: &str, matrix_file_name: Option<&str>) {
+        if let Some(file_name) = matrix_file_name {
+            self.matrix = read_matrix(&read_file(file_name), self.scale);
+        }
+
+        // TSPLIB files have sections; a first line with only numbers is the header of an
+        // explicit distance matrix in the same format as the penalty matrix
+        let content = read_file(tspp_file_name);
+        let first_line: Vec<&str> = content.lines().next().unwrap_or("").split_whitespace().collect();
+        if content.contains("_SECTION") {
+            self.read_tsplib(&content);
+        } else if !first_line.is_empty() && first_line.iter().all(|t| t.parse::<f64>().is_ok()) {
+            self.set_distance_matrix(read_matrix(&content, self.scale));
+        } else {
+            self.read_cities(tspp_file_name);
+            self.compute_distances();
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    // Cheaper direction of the edge {i, j}: relaxations built on undirected graphs (1-trees)
+    // stay valid on asymmetric instances when they use it
+    pub fn undirected_distance(&self, i: usize, j: usize) -> Cost {
+        self.distance(i, j).min(self.distance(j, i))
+    }
+
+    // Only the coordinates, without the n x n distance matrix (for very large instances)
+    pub fn read_cities(&mut self, tspp_file_name: &str) {
+        let content = read_file(tspp_file_name);
+        let lines: Vec<Vec<&str>> = content.lines()
+            .map(|line| line.split_whitespace().collect()).collect();
+    
+        for line in lines.iter().skip(1) {
+            let xf: f64 = line[1].parse().unwrap_or(0.0);
+            let yf: f64 = line[2].parse().unwrap_or(0.0);
+    
+            let city = City {
+                x: xf,
+                y: yf,
+            };
+    
+            self.cities.push(city);
+        }
+    }
+
+    // Coordinates in the format read by read_cities
+    pub fn write_cities(&self, file_name: &str, name: &str) {
+        let mut out = BufWriter::new(File::create(file_name).expect("Falha ao criar o arquivo"));
+        writeln!(out, "NAME {}", name).unwrap();
+        for (i, city) in self.cities.iter().enumerate() {
+            writeln!(out, "{} {} {}", i + 1, city.x, city.y).unwrap();
+        }
+    }
+
+    // Explicit distance matrix, read back by set_data as an instance
+    pub fn write_distances(&self, file_name: &str) {
+        let n = self.size();
+        let rows: Vec<Vec<Cost>> = (0..n).map(|i| (0..n).map(|j| self.distance(i, j)).collect()).collect();
+        self.write_matrix(file_name, &rows);
+    }
+
+    pub fn write_penalties(&self, file_name: &str) {
+        self.write_matrix(file_name, &self.matrix);
+    }
+
+    // A "rows columns" header and the rows, in the format of read_matrix
+    fn write_matrix(&self, file_name: &str, rows: &Vec<Vec<Cost>>) {
+        let mut out = BufWriter::new(File::create(file_name).expect("Falha ao criar o arquivo"));
+        writeln!(out, "{} {}", rows.len(), rows.first().map_or(0, |row| row.len())).unwrap();
+        for row in rows {
+            let values: Vec<String> = row.iter().map(|&value| self.format_cost(value)).collect();
+            writeln!(out, "{}", values.join(" ")).unwrap();
+        }
+    }
+
+    // Cost as printed: fractional when the costs are fixed point
+    pub fn format_cost(&self, cost: Cost) -> String {
+        if self.scale == 1 {
+            cost.to_string()
+        } else {
+            format!("{:.6}", cost as f64 / self.scale as f64)
+        }
+    }
+
+    pub fn cost_value(&self, cost: Cost) -> f64 {
+        cost as f64 / self.scale as f64
+    }
+
+    pub fn evaluate(&self, solution: &[usize]) -> Cost {
+        self.tour_distance(solution) + self.tour_penalty(solution)
+    }
+}
@@ -0,0 +1,155 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use std::time::Instant;
+use crate::{derive_seed, Instance};
+use crate::solution::{RunStats, Solution};
+use crate::solver::Params;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Topology {
+    // Island i sends its best tour to island i+1
+    Ring,
+    // Every island receives the best tour among all the others
+    FullyConnected,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Replacement {
+    // The immigrant always becomes the island's current tour
+    Always,
+    // The immigrant replaces the current tour only if it is better
+    IfBetter,
+}
+
+pub struct IslandConfig {
+    pub n_islands: usize,
+    pub epochs: usize,
+    pub migration_interval: usize,
+    pub topology: Topology,
+    pub replacement: Replacement,
+}
+
+impl Default for IslandConfig {
+    fn default() -> Self {
+        Self {
+            n_islands: 4,
+            epochs: 10,
+            migration_interval: 1,
+            topology: Topology::Ring,
+            replacement: Replacement::IfBetter,
+        }
+    }
+}
+
+impl IslandConfig {
+    pub const NAMES: [&'static str; 5] = ["islands", "epochs", "migration", "topology", "replacement"];
+
+    pub fn from_params(params: &Params) -> Self {
+        let default = IslandConfig::default();
+        Self {
+            n_islands: params.get_usize("islands", default.n_islands),
+            epochs: params.get_usize("epochs", default.epochs),
+            migration_interval: params.get_usize("migration", default.migration_interval),
+            topology: match params.get_string("topology").as_deref() {
+                None => default.topology,
+                Some("ring") => Topology::Ring,
+                Some("full") => Topology::FullyConnected,
+                Some(value) => panic!("Topologia desconhecida: {}", value),
+            },
+            replacement: match params.get_string("replacement").as_deref() {
+                None => default.replacement,
+                Some("always") => Replacement::Always,
+                Some("better") => Replacement::IfBetter,
+                Some(value) => panic!("Politica de substituicao desconhecida: {}", value),
+            },
+        }
+    }
+}
+
+struct Island {
+    current: Solution,
+    best: Solution,
+}
+
+impl Instance {
+    // Each epoch every island runs `solve(current, seed)` on its own thread; every
+    // `migration_interval` epochs the best tours migrate following the topology.
+    // Epochs are synchronous, so the result only depends on the seed.
+    pub fn islands<F>(&self, config: &IslandConfig, init_method: &str, seed: u64, solve: F) -> Solution
+    where F: Fn(&Vec<usize>, u64) -> Solution + Sync {
+        let start = Instant::now();
+        let mut stats = RunStats::default();
+        // Multi-start greedy seeds each island with a different one of its best tours
+        let mut top_tours = if init_method == "multi-start-greedy" {
+            let mut rng = StdRng::seed_from_u64(seed);
+            self.multi_start_greedy(None, config.n_islands, &mut rng)
+        } else {
+            vec![]
+        };
+        let mut islands: Vec<Island> = (0..config.n_islands).map(|i| {
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, i as u64));
+            let tour = if i < top_tours.len() {
+                std::mem::take(&mut top_tours[i])
+            } else {
+                self.construct(init_method, &mut rng)
+            };
+            let solution = self.solution(tour);
+            Island { current: solution.clone(), best: solution }
+        }).collect();
+
+        for epoch in 0..config.epochs {
+            let epoch_stats: Vec<RunStats> = islands.par_iter_mut().enumerate().map(|(i, island)| {
+                let island_seed = derive_seed(derive_seed(seed, i as u64), epoch as u64 + 1);
+                let solved = solve(&island.current.tour, island_seed);
+                let solution = self.solution(self.best_rotation(&solved.tour));
+                if solution.cost() < island.best.cost() {
+                    island.best = solution.clone();
+                }
+                island.current = solution;
+                solved.stats
+            }).collect();
+            for solved in &epoch_stats {
+                stats.include(solved);
+            }
+
+            if config.migration_interval > 0 && (epoch + 1) % config.migration_interval == 0 && epoch + 1 < config.epochs {
+                self.migrate(&mut islands, config);
+            }
+        }
+
+        let best = islands.into_iter()
+            .min_by_key(|island| island.best.cost())
+            .map(|island| island.best)
+            .unwrap_or_else(|| self.solution(self.sequential()));
+        stats.time = start.elapsed();
+        Solution { stats, ..best }
+    }
+
+    fn migrate(&self, islands: &mut [Island], config: &IslandConfig) {
+        let n = islands.len();
+        if n < 2 {
+            return;
+        }
+        let sources: Vec<usize> = (0..n).map(|i| match config.topology {
+            Topology::Ring => (i + n - 1) % n,
+            Topology::FullyConnected => (0..n).filter(|&j| j != i)
+                .min_by_key(|&j| (islands[j].best.cost(), j))
+                .unwrap(),
+        }).collect();
+
+        let migrants: Vec<Solution> = sources.iter().map(|&j| islands[j].best.clone()).collect();
+        for (island, migrant) in islands.iter_mut().zip(migrants) {
+            let accept = match config.replacement {
+                Replacement::Always => true,
+                Replacement::IfBetter => migrant.cost() < island.current.cost(),
+            };
+            if accept {
+                if migrant.cost() < island.best.cost() {
+                    island.best = migrant.clone();
+                }
+                island.current = migrant;
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// Instances, constructors, local search and metaheuristics for the static TSP, the TSP with
+// positional penalties (TSPP) and the moving-target TSP (MTTSP). The programs trab1, trab2
+// and trab3 are front-ends on top of this crate
+use std::fs::File;
+use std::io::Read;
+
+pub mod aco;
+pub mod analysis;
+pub mod args;
+pub mod bounds;
+pub mod checkpoint;
+pub mod constructors;
+pub mod distances;
+pub mod exact;
+pub mod generate;
+pub mod instance;
+pub mod islands;
+pub mod local_search;
+pub mod matching;
+pub mod metaheuristics;
+pub mod metrics;
+pub mod milp;
+pub mod mttsp;
+pub mod neighborhoods;
+pub mod report;
+pub mod rotation;
+pub mod solution;
+pub mod solver;
+pub mod tour;
+pub mod tsplib;
+pub mod tuning;
+
+// The static TSP is a TSPP instance without a penalty matrix
+pub use instance::{City, Cost, Instance, EXACT_SCALE};
+
+pub fn read_file(file_name: &str) -> String {
+    let mut file = File::open(file_name)
+        .expect("Falha ao abrir o arquivo");
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .expect("Falha ao ler o conteudo do arquivo");
+
+    content
+}
+
+// Independent seed for each (seed, stream) pair (splitmix64), so parallel work gives the
+// same result for a seed regardless of how it is scheduled
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed ^ stream.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,129 @@
+use std::time::Instant;
+use crate::{Cost, Instance};
+use crate::solution::{RunStats, Solution};
+use crate::tour::{ArrayTour, Tour, TwoLevelTour, TWO_LEVEL_MIN_CITIES};
+
+impl Instance {
+    pub fn local_search(&self, init: &[usize]) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { iterations: 1, ..RunStats::default() };
+        let mut solution = self.two_opt(init);
+        stats.include(&solution.stats);
+        // 2-opt reverses a segment, which changes its cost on asymmetric instances, so there it
+        // is alternated with neighborhoods that keep the direction of every edge
+        if !self.symmetric {
+            loop {
+                stats.iterations += 1;
+                let eval = solution.cost();
+                solution = self.or_opt(&solution.tour);
+                stats.include(&solution.stats);
+                if self.matrix.is_empty() {
+                    solution = self.segment_exchange(&solution.tour);
+                    stats.include(&solution.stats);
+                }
+                solution = self.two_opt(&solution.tour);
+                stats.include(&solution.stats);
+                if solution.cost() >= eval {
+                    break;
+                }
+            }
+        }
+        stats.time = start.elapsed();
+        Solution { stats, ..solution }
+    }
+
+    pub fn two_opt(&self, init: &[usize]) -> Solution {
+        if init.len() >= TWO_LEVEL_MIN_CITIES {
+            self.two_opt_with::<TwoLevelTour>(init)
+        } else {
+            self.two_opt_with::<ArrayTour>(init)
+        }
+    }
+
+    // Best improvement over the reversals of paths that leave init[0] in place
+    fn two_opt_with<T: Tour>(&self, init: &[usize]) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let mut tour = T::new(init);
+        let first = init[0];
+        let mut eval_first = self.evaluate(init);
+        let mut eval_temp: Cost;
+        let has_penalty = !self.matrix.is_empty();
+        let size = tour.size();
+        loop {
+            stats.iterations += 1;
+            let mut better_option = (first, first);
+            let mut eval_better_option = Cost::MAX;
+            // a and b are the cities at positions i and j
+            let mut a = tour.next(first);
+            for i in 1..size-1 {
+                let before = tour.prev(a);
+                // Change in cost of the edges inside the path a..b when they are reversed
+                let mut reversal = 0;
+                let mut b = a;
+                let mut after = tour.next(a);
+                for j in i+1..size {
+                    let previous = b;
+                    b = after;
+                    after = tour.next(b);
+                    if !self.symmetric {
+                        reversal += self.distance(b, previous) - self.distance(previous, b);
+                    }
+                    eval_temp = eval_first;
+                    eval_temp -= self.distance(before, a);
+                    eval_temp += self.distance(before, b);
+                    eval_temp -= self.distance(b, after);
+                    eval_temp += self.distance(a, after);
+                    eval_temp += reversal;
+                    stats.evaluations += 1;
+                    if has_penalty {
+                        eval_temp += self.penalty_delta(&tour, a, i, j);
+                    }
+                    if eval_temp < eval_better_option {
+                        better_option = (a, b);
+                        eval_better_option = eval_temp;
+                    }
+                }
+                a = tour.next(a);
+            }
+            if eval_better_option < eval_first {
+                debug_assert!(!tour.between(better_option.0, first, better_option.1));
+                tour.flip(better_option.0, better_option.1);
+                eval_first = eval_better_option;
+            } else {
+                stats.time = start.elapsed();
+                return Solution { stats, ..self.solution(tour.order_from(first)) };
+            }
+        }
+    }
+
+    // Change in penalty when the cities at positions i..=j, the first of them a, are reversed
+    pub(crate) fn penalty_delta<T: Tour>(&self, tour: &T, a: usize, i: usize, j: usize) -> Cost {
+        let mut delta = 0;
+        let mut city = a;
+        for t in 0..=j-i {
+            delta += self.matrix[city][j - t] - self.matrix[city][i + t];
+            city = tour.next(city);
+        }
+        delta
+    }
+
+    // Change in distance when the path that goes forward from a to b is reversed
+    pub(crate) fn flip_delta<T: Tour>(&self, tour: &T, a: usize, b: usize) -> Cost {
+        let (before, after) = (tour.prev(a), tour.next(b));
+        let mut delta = 0;
+        if !self.symmetric {
+            let mut city = a;
+            while city != b {
+                let next = tour.next(city);
+                delta += self.distance(next, city) - self.distance(city, next);
+                city = next;
+            }
+        }
+        if after == a {
+            // The whole tour: the closing edge b -> a is reversed too
+            return delta + self.distance(a, b) - self.distance(b, a);
+        }
+        delta + self.distance(before, b) + self.distance(a, after) - self.distance(before, a) - self.distance(b, after)
+    }
+}
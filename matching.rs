@@ -0,0 +1,421 @@
+use std::collections::VecDeque;
+
+// Maximum weight matching on a general graph (Edmonds' blossom algorithm with dual
+// variables, O(n^3)). Vertices are 1-indexed internally and 0 means "none";
+// indices above n are blossoms.
+
+#[derive(Clone, Copy)]
+struct Edge {
+    u: usize,
+    v: usize,
+    w: i64,
+}
+
+struct WeightedBlossom {
+    n: usize,
+    n_x: usize,
+    g: Vec<Vec<Edge>>,
+    lab: Vec<i64>,
+    mate: Vec<usize>,
+    slack: Vec<usize>,
+    st: Vec<usize>,
+    pa: Vec<usize>,
+    flower_from: Vec<Vec<usize>>,
+    s: Vec<i32>,
+    vis: Vec<usize>,
+    flower: Vec<Vec<usize>>,
+    queue: VecDeque<usize>,
+    timestamp: usize,
+}
+
+impl WeightedBlossom {
+    fn new(weights: &[Vec<i64>]) -> Self {
+        let n = weights.len();
+        let size = 2 * n + 1;
+        let is_vertex = |u: usize| (1..=n).contains(&u);
+        let g: Vec<Vec<Edge>> = (0..size)
+            .map(|u| (0..size)
+                .map(|v| {
+                    let w = if u != v && is_vertex(u) && is_vertex(v) { weights[u-1][v-1] } else { 0 };
+                    Edge { u, v, w }
+                })
+                .collect())
+            .collect();
+        let mut flower_from = vec![vec![0; n + 1]; size];
+        for (u, from) in flower_from.iter_mut().enumerate().take(n + 1).skip(1) {
+            from[u] = u;
+        }
+        let st: Vec<usize> = (0..size).map(|u| if u <= n { u } else { 0 }).collect();
+        let w_max = weights.iter().flatten().cloned().max().unwrap_or(0);
+        let lab: Vec<i64> = (0..size).map(|u| if is_vertex(u) { w_max } else { 0 }).collect();
+        Self {
+            n,
+            n_x: n,
+            g,
+            lab,
+            mate: vec![0; size],
+            slack: vec![0; size],
+            st,
+            pa: vec![0; size],
+            flower_from,
+            s: vec![-1; size],
+            vis: vec![0; size],
+            flower: vec![vec![]; size],
+            queue: VecDeque::new(),
+            timestamp: 0,
+        }
+    }
+
+    fn dist(&self, e: Edge) -> i64 {
+        self.lab[e.u] + self.lab[e.v] - e.w * 2
+    }
+
+    fn update_slack(&mut self, u: usize, x: usize) {
+        if self.slack[x] == 0 || self.dist(self.g[u][x]) < self.dist(self.g[self.slack[x]][x]) {
+            self.slack[x] = u;
+        }
+    }
+
+    fn set_slack(&mut self, x: usize) {
+        self.slack[x] = 0;
+        for u in 1..=self.n {
+            if self.g[u][x].w > 0 && self.st[u] != x && self.s[self.st[u]] == 0 {
+                self.update_slack(u, x);
+            }
+        }
+    }
+
+    fn queue_push(&mut self, x: usize) {
+        if x <= self.n {
+            self.queue.push_back(x);
+        } else {
+            for y in self.flower[x].clone() {
+                self.queue_push(y);
+            }
+        }
+    }
+
+    fn set_st(&mut self, x: usize, b: usize) {
+        self.st[x] = b;
+        if x > self.n {
+            for y in self.flower[x].clone() {
+                self.set_st(y, b);
+            }
+        }
+    }
+
+    fn get_pr(&mut self, b: usize, xr: usize) -> usize {
+        let pr = self.flower[b].iter().position(|&x| x == xr).unwrap();
+        if pr % 2 == 1 {
+            self.flower[b][1..].reverse();
+            self.flower[b].len() - pr
+        } else {
+            pr
+        }
+    }
+
+    fn set_match(&mut self, u: usize, v: usize) {
+        self.mate[u] = self.g[u][v].v;
+        if u > self.n {
+            let e = self.g[u][v];
+            let xr = self.flower_from[u][e.u];
+            let pr = self.get_pr(u, xr);
+            for i in 0..pr {
+                let (a, b) = (self.flower[u][i], self.flower[u][i ^ 1]);
+                self.set_match(a, b);
+            }
+            self.set_match(xr, v);
+            self.flower[u].rotate_left(pr);
+        }
+    }
+
+    fn augment(&mut self, mut u: usize, mut v: usize) {
+        loop {
+            let xnv = self.st[self.mate[u]];
+            self.set_match(u, v);
+            if xnv == 0 {
+                return;
+            }
+            let next = self.st[self.pa[xnv]];
+            self.set_match(xnv, next);
+            u = next;
+            v = xnv;
+        }
+    }
+
+    fn get_lca(&mut self, mut u: usize, mut v: usize) -> usize {
+        self.timestamp += 1;
+        while u != 0 || v != 0 {
+            if u != 0 {
+                if self.vis[u] == self.timestamp {
+                    return u;
+                }
+                self.vis[u] = self.timestamp;
+                u = self.st[self.mate[u]];
+                if u != 0 {
+                    u = self.st[self.pa[u]];
+                }
+            }
+            std::mem::swap(&mut u, &mut v);
+        }
+        0
+    }
+
+    fn add_blossom(&mut self, u: usize, lca: usize, v: usize) {
+        let mut b = self.n + 1;
+        while b <= self.n_x && self.st[b] != 0 {
+            b += 1;
+        }
+        if b > self.n_x {
+            self.n_x += 1;
+        }
+        self.lab[b] = 0;
+        self.s[b] = 0;
+        self.mate[b] = self.mate[lca];
+        self.flower[b] = vec![lca];
+        let mut x = u;
+        while x != lca {
+            let y = self.st[self.mate[x]];
+            self.flower[b].push(x);
+            self.flower[b].push(y);
+            self.queue_push(y);
+            x = self.st[self.pa[y]];
+        }
+        self.flower[b][1..].reverse();
+        x = v;
+        while x != lca {
+            let y = self.st[self.mate[x]];
+            self.flower[b].push(x);
+            self.flower[b].push(y);
+            self.queue_push(y);
+            x = self.st[self.pa[y]];
+        }
+        self.set_st(b, b);
+        for x in 1..=self.n_x {
+            self.g[b][x].w = 0;
+            self.g[x][b].w = 0;
+        }
+        for x in 1..=self.n {
+            self.flower_from[b][x] = 0;
+        }
+        for xs in self.flower[b].clone() {
+            for x in 1..=self.n_x {
+                if self.g[b][x].w == 0 || self.dist(self.g[xs][x]) < self.dist(self.g[b][x]) {
+                    self.g[b][x] = self.g[xs][x];
+                    self.g[x][b] = self.g[x][xs];
+                }
+            }
+            for x in 1..=self.n {
+                if self.flower_from[xs][x] != 0 {
+                    self.flower_from[b][x] = xs;
+                }
+            }
+        }
+        self.set_slack(b);
+    }
+
+    fn expand_blossom(&mut self, b: usize) {
+        for xs in self.flower[b].clone() {
+            self.set_st(xs, xs);
+        }
+        let xr = self.flower_from[b][self.g[b][self.pa[b]].u];
+        let pr = self.get_pr(b, xr);
+        let mut i = 0;
+        while i < pr {
+            let xs = self.flower[b][i];
+            let xns = self.flower[b][i+1];
+            self.pa[xs] = self.g[xns][xs].u;
+            self.s[xs] = 1;
+            self.s[xns] = 0;
+            self.slack[xs] = 0;
+            self.set_slack(xns);
+            self.queue_push(xns);
+            i += 2;
+        }
+        self.s[xr] = 1;
+        self.pa[xr] = self.pa[b];
+        for i in pr+1..self.flower[b].len() {
+            let xs = self.flower[b][i];
+            self.s[xs] = -1;
+            self.set_slack(xs);
+        }
+        self.st[b] = 0;
+    }
+
+    fn on_found_edge(&mut self, e: Edge) -> bool {
+        let u = self.st[e.u];
+        let v = self.st[e.v];
+        if self.s[v] == -1 {
+            self.pa[v] = e.u;
+            self.s[v] = 1;
+            let nu = self.st[self.mate[v]];
+            self.slack[v] = 0;
+            self.slack[nu] = 0;
+            self.s[nu] = 0;
+            self.queue_push(nu);
+        } else if self.s[v] == 0 {
+            let lca = self.get_lca(u, v);
+            if lca == 0 {
+                self.augment(u, v);
+                self.augment(v, u);
+                return true;
+            }
+            self.add_blossom(u, lca, v);
+        }
+        false
+    }
+
+    fn matching(&mut self) -> bool {
+        for x in 1..=self.n_x {
+            self.s[x] = -1;
+            self.slack[x] = 0;
+        }
+        self.queue.clear();
+        for x in 1..=self.n_x {
+            if self.st[x] == x && self.mate[x] == 0 {
+                self.pa[x] = 0;
+                self.s[x] = 0;
+                self.queue_push(x);
+            }
+        }
+        if self.queue.is_empty() {
+            return false;
+        }
+        loop {
+            while let Some(u) = self.queue.pop_front() {
+                if self.s[self.st[u]] == 1 {
+                    continue;
+                }
+                for v in 1..=self.n {
+                    if self.g[u][v].w > 0 && self.st[u] != self.st[v] {
+                        if self.dist(self.g[u][v]) == 0 {
+                            if self.on_found_edge(self.g[u][v]) {
+                                return true;
+                            }
+                        } else {
+                            let sv = self.st[v];
+                            self.update_slack(u, sv);
+                        }
+                    }
+                }
+            }
+
+            let mut d = i64::MAX;
+            for b in self.n+1..=self.n_x {
+                if self.st[b] == b && self.s[b] == 1 {
+                    d = d.min(self.lab[b] / 2);
+                }
+            }
+            for x in 1..=self.n_x {
+                if self.st[x] == x && self.slack[x] != 0 {
+                    let slack_dist = self.dist(self.g[self.slack[x]][x]);
+                    if self.s[x] == -1 {
+                        d = d.min(slack_dist);
+                    } else if self.s[x] == 0 {
+                        d = d.min(slack_dist / 2);
+                    }
+                }
+            }
+            for u in 1..=self.n {
+                if self.s[self.st[u]] == 0 {
+                    if self.lab[u] <= d {
+                        return false;
+                    }
+                    self.lab[u] -= d;
+                } else if self.s[self.st[u]] == 1 {
+                    self.lab[u] += d;
+                }
+            }
+            for b in self.n+1..=self.n_x {
+                if self.st[b] == b {
+                    if self.s[b] == 0 {
+                        self.lab[b] += d * 2;
+                    } else if self.s[b] == 1 {
+                        self.lab[b] -= d * 2;
+                    }
+                }
+            }
+
+            self.queue.clear();
+            for x in 1..=self.n_x {
+                if self.st[x] == x && self.slack[x] != 0 && self.st[self.slack[x]] != x
+                    && self.dist(self.g[self.slack[x]][x]) == 0
+                    && self.on_found_edge(self.g[self.slack[x]][x]) {
+                    return true;
+                }
+            }
+            for b in self.n+1..=self.n_x {
+                if self.st[b] == b && self.s[b] == 1 && self.lab[b] == 0 {
+                    self.expand_blossom(b);
+                }
+            }
+        }
+    }
+}
+
+// `weights[u][v]` > 0 for every edge of the graph, 0 when there is no edge. Returns the mate of
+// each vertex (None if unmatched) of a matching of maximum total weight
+pub fn maximum_weight_matching(weights: &[Vec<i64>]) -> Vec<Option<usize>> {
+    let mut blossom = WeightedBlossom::new(weights);
+    while blossom.matching() {}
+    (1..=blossom.n)
+        .map(|u| if blossom.mate[u] == 0 { None } else { Some(blossom.mate[u] - 1) })
+        .collect()
+}
+
+// Minimum cost perfect matching on a complete graph with an even number of vertices. Costs are
+// turned into weights big enough that every perfect matching outweighs any imperfect one
+pub fn minimum_perfect_matching(costs: &[Vec<i64>]) -> Vec<usize> {
+    let n = costs.len();
+    let max_cost = costs.iter().flatten().cloned().max().unwrap_or(0);
+    let big = max_cost * (n as i64 / 2 + 1) + 1;
+    let weights: Vec<Vec<i64>> = (0..n)
+        .map(|u| (0..n).map(|v| if u == v { 0 } else { big - costs[u][v] }).collect())
+        .collect();
+    maximum_weight_matching(&weights).iter()
+        .map(|m| m.expect("Emparelhamento perfeito nao encontrado"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    // Cost of the cheapest perfect matching of `free`, trying every partner of the first vertex
+    fn brute_force(costs: &[Vec<i64>], free: &mut Vec<usize>) -> i64 {
+        if free.is_empty() {
+            return 0;
+        }
+        let u = free.remove(0);
+        let mut best = i64::MAX;
+        for k in 0..free.len() {
+            let v = free.remove(k);
+            best = best.min(costs[u][v] + brute_force(costs, free));
+            free.insert(k, v);
+        }
+        free.insert(0, u);
+        best
+    }
+
+    #[test]
+    fn minimum_perfect_matching_is_optimal() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for trial in 0..200 {
+            let n = 2 * (1 + trial % 5);
+            let random: Vec<Vec<i64>> = (0..n).map(|_| (0..n).map(|_| rng.gen_range(1..50)).collect()).collect();
+            let costs: Vec<Vec<i64>> = (0..n)
+                .map(|u| (0..n).map(|v| if u == v { 0 } else { random[u.min(v)][u.max(v)] }).collect())
+                .collect();
+
+            let mate = minimum_perfect_matching(&costs);
+            for u in 0..n {
+                assert_ne!(mate[u], u);
+                assert_eq!(mate[mate[u]], u);
+            }
+            let cost: i64 = (0..n).filter(|&u| u < mate[u]).map(|u| costs[u][mate[u]]).sum();
+            assert_eq!(cost, brute_force(&costs, &mut (0..n).collect()));
+        }
+    }
+}
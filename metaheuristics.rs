@@ -0,0 +1,432 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use std::time::Instant;
+use crate::{derive_seed, Cost, Instance};
+use crate::islands::IslandConfig;
+use crate::solution::{RunStats, Solution};
+use crate::tour::{ArrayTour, Tour, TwoLevelTour, TWO_LEVEL_MIN_CITIES};
+use crate::solver::{Budget, Params, Registry, Solver};
+
+pub struct SaParams {
+    pub temp: f64,
+    pub alfa: f64,
+    pub freeze: f64,
+    // Moves tried at each temperature; twice the number of cities when not given
+    pub moves: Option<usize>,
+}
+
+impl SaParams {
+    const NAMES: [&'static str; 4] = ["temp", "alfa", "freeze", "moves"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            temp: params.get("temp", 10000.0),
+            alfa: params.get("alfa", 0.9999),
+            freeze: params.get("freeze", 0.01),
+            moves: params.get_string("moves").map(|_| params.get_usize("moves", 0)),
+        }
+    }
+}
+
+pub struct IlsParams {
+    pub iterations: usize,
+    // Random swaps of each perturbation, as a fraction of the number of cities
+    pub perturbation: f64,
+}
+
+impl IlsParams {
+    const NAMES: [&'static str; 2] = ["iterations", "perturbation"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            iterations: params.get_usize("iterations", 10000),
+            perturbation: params.get("perturbation", 0.02),
+        }
+    }
+}
+
+pub struct GraspParams {
+    pub iterations: usize,
+    // Restricted candidate list, as a fraction of the unvisited cities
+    pub rcl: f64,
+}
+
+impl GraspParams {
+    const NAMES: [&'static str; 2] = ["iterations", "rcl"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            iterations: params.get_usize("iterations", 500),
+            rcl: params.get("rcl", 0.2),
+        }
+    }
+}
+
+// How the initial tours of multi-start and island solvers are built
+pub struct StartParams {
+    pub starts: usize,
+    pub init: String,
+}
+
+impl StartParams {
+    const NAMES: [&'static str; 2] = ["starts", "init"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            starts: params.get_usize("starts", 1),
+            init: params.get_string("init").unwrap_or("random".to_string()),
+        }
+    }
+}
+
+impl Instance {
+    fn grasp_iteration(&self, rcl: f64, rng: &mut StdRng) -> Solution {
+        let mut choice: usize;
+        let mut visited = vec![false; self.size()];
+        let mut current: usize = rng.gen_range(0..self.size());
+        visited[current] = true;
+        let mut solution = vec![current];
+        let mut current_distances: Vec<(usize, Cost)>;
+
+        current_distances = (0..self.size()).map(|c| (c, self.distance(current, c)))
+                .filter(|&c| !visited[c.0])
+                .collect();
+        while !current_distances.is_empty() {
+            current_distances.sort_by_key(|a| a.1);
+            choice = rng.gen_range(0..=((current_distances.len() as f64 * rcl).floor() as usize));
+            let next_city = current_distances[choice].0;
+            visited[next_city] = true;
+            solution.push(next_city);
+
+            current = current_distances[choice].0;
+            current_distances = (0..self.size()).map(|c| (c, self.distance(current, c)))
+                .filter(|&c| !visited[c.0])
+                .collect();
+        }
+        self.local_search(&solution)
+    }
+
+    pub fn grasp(&self, params: &GraspParams, budget: &Budget, seed: u64) -> Solution {
+        let start = Instant::now();
+        let n_iter = budget.iterations_or(params.iterations as u64);
+        let mut stats = RunStats::default();
+        let mut best_solution = self.solution(self.sequential());
+
+        // Iterations not started when the time runs out are skipped
+        let iterations: Vec<Solution> = (0..n_iter).into_par_iter().filter_map(|i| {
+            if budget.expired(start) {
+                return None;
+            }
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, i));
+            Some(self.grasp_iteration(params.rcl, &mut rng))
+        }).collect();
+        stats.iterations = iterations.len() as u64;
+        for solution in &iterations {
+            stats.include(&solution.stats);
+        }
+        // Ties go to the lowest index, so the result depends only on the seed. Without any
+        // iteration the sequential tour is returned
+        if let Some(solution) = iterations.into_iter().min_by_key(|solution| solution.cost()) {
+            if best_solution.cost() > solution.cost() {
+                best_solution = solution;
+            }
+        }
+        stats.time = start.elapsed();
+        Solution { stats, ..best_solution }
+    }
+
+    pub fn sa(&self, init: &[usize], params: &SaParams, budget: &Budget, seed: u64) -> Solution {
+        if init.len() >= TWO_LEVEL_MIN_CITIES {
+            self.sa_with::<TwoLevelTour>(init, params, budget, seed)
+        } else {
+            self.sa_with::<ArrayTour>(init, params, budget, seed)
+        }
+    }
+
+    // The budget counts moves; the time is checked at each temperature
+    fn sa_with<T: Tour>(&self, init: &[usize], params: &SaParams, budget: &Budget, seed: u64) -> Solution {
+        let start = Instant::now();
+        let max_iter = params.moves.unwrap_or(init.len() * 2);
+        let max_moves = budget.iterations_or(u64::MAX);
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tour = T::new(init);
+        // City at index 0 of the solution; it changes when a reversal starts there
+        let mut first = init[0];
+        let len = init.len();
+        let has_penalty = !self.matrix.is_empty();
+        let mut best_solution = init.to_vec();
+        let mut eval_best: Cost = self.evaluate(&best_solution);
+        let mut eval_solution = eval_best;
+        let mut temperature = params.temp;
+        let mut delta: Cost;
+        while temperature > params.freeze && stats.iterations < max_moves && !budget.expired(start) {
+            
+            let mut i: usize; let mut j: usize;
+            for _ in 0..max_iter {
+                if stats.iterations >= max_moves {
+                    break;
+                }
+                i = rng.gen_range(0..len);
+                j = rng.gen_range(0..len);
+                while i == j {
+                    j = rng.gen_range(0..len);
+                }
+                if i > j { (i,j) = (j,i); }
+                stats.iterations += 1;
+                stats.evaluations += 1;
+                // Reversing solution[i..=j]
+                let (a, b) = (tour.nth(first, i), tour.nth(first, j));
+                let neighbor_first = if i == 0 { b } else { first };
+                delta = self.flip_delta(&tour, a, b);
+                if has_penalty {
+                    delta += self.penalty_delta(&tour, a, i, j);
+                }
+                // Temperatures are in cost units, whatever the fixed point scale
+                let accept = if delta < 0 {
+                    true
+                } else {
+                    let x = rng.gen_range(0.0..=1.0);
+                    x < (std::f64::consts::E.powf((-delta as f64) / self.scale as f64 / temperature))
+                };
+                if accept {
+                    tour.flip(a, b);
+                    first = neighbor_first;
+                    eval_solution += delta;
+                    if eval_solution < eval_best {
+                        best_solution = tour.order_from(first);
+                        eval_best = eval_solution;
+                    }
+                }
+            }
+            temperature *= params.alfa;
+        }
+        let solution = self.local_search(&best_solution);
+        stats.include(&solution.stats);
+        stats.time = start.elapsed();
+        Solution { stats, ..solution }
+    }
+
+    pub fn ils(&self, init: &[usize], params: &IlsParams, budget: &Budget, seed: u64) -> Solution {
+        let start = Instant::now();
+        let n_iter = budget.iterations_or(params.iterations as u64);
+        let mut stats = RunStats::default();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut i: usize;
+        let mut j: usize;
+        let len = init.len();
+        let pert_strength = (len as f64 * params.perturbation).ceil() as i32;
+        let mut solution = init.to_vec();
+        let mut best_solution = self.solution(init.to_vec());
+
+        for _ in 0..n_iter {
+            if budget.expired(start) {
+                break;
+            }
+            stats.iterations += 1;
+            let local_optimum = self.local_search(&solution);
+            stats.include(&local_optimum.stats);
+
+            if best_solution.cost() > local_optimum.cost() {
+                best_solution = local_optimum.clone();
+            }
+            solution = local_optimum.tour;
+
+            for _ in 0..pert_strength {
+                i = rng.gen_range(0..len);
+                j = rng.gen_range(0..len);
+                while i == j {
+                    j = rng.gen_range(0..len);
+                }
+                (solution[i], solution[j]) = (solution[j], solution[i]);
+            }
+        }
+        stats.time = start.elapsed();
+        Solution { stats, ..best_solution }
+    }
+
+    // Runs `solve(init, seed)` from `starts` tours built by `init_method` in parallel and keeps
+    // the best, ties broken by start index so the result depends only on the seed
+    pub fn multi_start<F>(&self, starts: usize, init_method: &str, seed: u64, solve: F) -> Solution
+    where F: Fn(&Vec<usize>, u64) -> Solution + Sync {
+        // At least one start, so there is always a tour to return
+        let starts = starts.max(1);
+        let start = Instant::now();
+        let mut stats = RunStats::default();
+        let runs: Vec<Solution> = (0..starts).into_par_iter().map(|s| {
+            let start_seed = derive_seed(seed, s as u64);
+            let mut rng = StdRng::seed_from_u64(start_seed);
+            let init = self.construct(init_method, &mut rng);
+            let solved = solve(&init, derive_seed(start_seed, 0));
+            Solution { stats: solved.stats, ..self.solution(self.best_rotation(&solved.tour)) }
+        }).collect();
+        for run in &runs {
+            stats.include(&run.stats);
+        }
+        let best = runs.into_iter().min_by_key(|run| run.cost()).unwrap();
+        stats.time = start.elapsed();
+        Solution { stats, ..best }
+    }
+}
+
+fn check(params: &Params, solver: &str, groups: &[&[&str]]) {
+    params.check(solver, &groups.concat());
+}
+
+// Share of the budget of each epoch of an island model
+fn per_epoch(budget: &Budget, epochs: usize) -> Budget {
+    let epochs = epochs.max(1);
+    Budget {
+        iterations: budget.iterations.map(|iterations| (iterations / epochs as u64).max(1)),
+        time: budget.time.map(|limit| limit / epochs as u32),
+    }
+}
+
+struct Grasp {
+    params: GraspParams,
+}
+
+impl Solver<Instance, Solution> for Grasp {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        instance.grasp(&self.params, budget, rng.gen())
+    }
+}
+
+struct SimulatedAnnealing {
+    params: SaParams,
+    start: StartParams,
+}
+
+impl Solver<Instance, Solution> for SimulatedAnnealing {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        instance.multi_start(self.start.starts, &self.start.init, rng.gen(), |init, seed| {
+            instance.sa(init, &self.params, budget, seed)
+        })
+    }
+}
+
+struct Ils {
+    params: IlsParams,
+    start: StartParams,
+}
+
+impl Solver<Instance, Solution> for Ils {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        instance.multi_start(self.start.starts, &self.start.init, rng.gen(), |init, seed| {
+            instance.ils(init, &self.params, budget, seed)
+        })
+    }
+}
+
+struct SaIslands {
+    params: SaParams,
+    islands: IslandConfig,
+    init: String,
+}
+
+impl Solver<Instance, Solution> for SaIslands {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        // Every epoch runs a full cooling schedule
+        let epoch_budget = per_epoch(budget, self.islands.epochs);
+        instance.islands(&self.islands, &self.init, rng.gen(), |init, seed| {
+            instance.sa(init, &self.params, &epoch_budget, seed)
+        })
+    }
+}
+
+struct IlsIslands {
+    params: IlsParams,
+    islands: IslandConfig,
+    init: String,
+}
+
+impl Solver<Instance, Solution> for IlsIslands {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        // The iterations are shared among the epochs
+        let total = Budget { iterations: Some(budget.iterations_or(self.params.iterations as u64)), ..*budget };
+        let epoch_budget = per_epoch(&total, self.islands.epochs);
+        instance.islands(&self.islands, &self.init, rng.gen(), |init, seed| {
+            instance.ils(init, &self.params, &epoch_budget, seed)
+        })
+    }
+}
+
+struct Exact {
+    incumbent: IlsParams,
+}
+
+impl Solver<Instance, Solution> for Exact {
+    // The budget only limits the ILS that finds the incumbent; the proof runs to the end
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        let incumbent = instance.ils(&instance.greedy(), &self.incumbent, budget, rng.gen());
+        let exact = instance.exact(&incumbent.tour);
+        let stats = RunStats { proof: Some(exact.proof), ..incumbent.stats };
+        Solution { stats, ..instance.solution(exact.tour) }
+    }
+}
+
+pub fn registry() -> Registry<Instance, Solution> {
+    let mut registry = Registry::new();
+    registry.register("grasp", |params| {
+        check(params, "grasp", &[&GraspParams::NAMES]);
+        Box::new(Grasp { params: GraspParams::from_params(params) })
+    });
+    registry.register("sa", |params| {
+        check(params, "sa", &[&SaParams::NAMES, &StartParams::NAMES]);
+        Box::new(SimulatedAnnealing { params: SaParams::from_params(params), start: StartParams::from_params(params) })
+    });
+    registry.register("ils", |params| {
+        check(params, "ils", &[&IlsParams::NAMES, &StartParams::NAMES]);
+        Box::new(Ils { params: IlsParams::from_params(params), start: StartParams::from_params(params) })
+    });
+    registry.register("sa-islands", |params| {
+        check(params, "sa-islands", &[&SaParams::NAMES, &IslandConfig::NAMES, &["init"]]);
+        Box::new(SaIslands {
+            params: SaParams::from_params(params),
+            islands: IslandConfig::from_params(params),
+            init: StartParams::from_params(params).init,
+        })
+    });
+    registry.register("ils-islands", |params| {
+        check(params, "ils-islands", &[&IlsParams::NAMES, &IslandConfig::NAMES, &["init"]]);
+        Box::new(IlsIslands {
+            params: IlsParams::from_params(params),
+            islands: IslandConfig::from_params(params),
+            init: StartParams::from_params(params).init,
+        })
+    });
+    registry.register("exact", |params| {
+        check(params, "exact", &[&["incumbent-iterations"]]);
+        let incumbent = IlsParams { iterations: params.get_usize("incumbent-iterations", 1000), perturbation: 0.02 };
+        Box::new(Exact { incumbent })
+    });
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::City;
+    use crate::solution::check_permutation;
+
+    #[test]
+    fn every_solver_returns_a_tour_on_a_zero_budget() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let n = 12;
+        let mut instance = Instance::new();
+        instance.cities = (0..n).map(|_| City { x: rng.gen_range(0.0..100.0), y: rng.gen_range(0.0..100.0) }).collect();
+        instance.compute_distances();
+        instance.matrix = (0..n).map(|_| (0..n).map(|_| rng.gen_range(0..50)).collect()).collect();
+
+        let zero = Budget { iterations: Some(0), time: Some(Duration::ZERO) };
+        let registry = registry();
+        for name in registry.names() {
+            let solver = registry.build(name, &Params::default()).unwrap();
+            let solution = solver.solve(&instance, &mut StdRng::seed_from_u64(1), &zero);
+            assert_eq!(check_permutation(&solution.tour, n), Ok(()), "{}", name);
+            assert_eq!(solution.cost(), instance.evaluate(&solution.tour), "{}", name);
+        }
+    }
+}
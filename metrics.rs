@@ -0,0 +1,76 @@
+use crate::{City, Cost};
+
+const EARTH_RADIUS: f64 = 6371.0;
+// Radius of the idealized sphere of the TSPLIB GEO instances
+const TSPLIB_EARTH_RADIUS: f64 = 6378.388;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    Euclidean,
+    // |dx| + |dy|
+    Manhattan,
+    // max(|dx|, |dy|)
+    Chebyshev,
+    // Great-circle distance in km, with x = latitude and y = longitude in decimal degrees
+    Haversine,
+    // TSPLIB GEO: x and y in DDD.MM (degrees and minutes)
+    Geo,
+}
+
+// TSPLIB truncates pi for GEO, and the published optima are computed with it
+#[allow(clippy::approx_constant)]
+const TSPLIB_PI: f64 = 3.141592;
+
+// DDD.MM to radians, as specified by TSPLIB
+fn geo_radians(value: f64) -> f64 {
+    let degrees = value.trunc();
+    let minutes = value - degrees;
+    TSPLIB_PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+impl Metric {
+    pub fn from_name(name: &str) -> Metric {
+        match name {
+            "euclidean" => Metric::Euclidean,
+            "manhattan" => Metric::Manhattan,
+            "chebyshev" => Metric::Chebyshev,
+            "haversine" => Metric::Haversine,
+            "geo" => Metric::Geo,
+            _ => panic!("Metrica desconhecida: {}", name),
+        }
+    }
+
+    pub fn distance(&self, a: &City, b: &City) -> f64 {
+        let (dx, dy) = ((b.x - a.x).abs(), (b.y - a.y).abs());
+        match self {
+            Metric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Haversine => {
+                let (lat_a, lat_b) = (a.x.to_radians(), b.x.to_radians());
+                let h = ((lat_b - lat_a) / 2.0).sin().powi(2)
+                    + lat_a.cos() * lat_b.cos() * ((b.y - a.y).to_radians() / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+            },
+            Metric::Geo => {
+                let (lat_a, lon_a) = (geo_radians(a.x), geo_radians(a.y));
+                let (lat_b, lon_b) = (geo_radians(b.x), geo_radians(b.y));
+                let q1 = (lon_a - lon_b).cos();
+                let q2 = (lat_a - lat_b).cos();
+                let q3 = (lat_a + lat_b).cos();
+                TSPLIB_EARTH_RADIUS * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).clamp(-1.0, 1.0).acos()
+            },
+        }
+    }
+
+    // Distance in units of 1/scale. With scale 1 distances are rounded to the nearest integer,
+    // except GEO, which TSPLIB truncates after adding 1
+    pub fn cost(&self, a: &City, b: &City, scale: Cost) -> Cost {
+        let distance = self.distance(a, b);
+        if scale == 1 && *self == Metric::Geo {
+            (distance + 1.0) as Cost
+        } else {
+            (distance * scale as f64).round() as Cost
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::{read_file, Instance};
+
+// MILP model of the objective of `evaluate`:
+//   x_i_k = 1 if city i is at position k, a_i_j = 1 if the tour goes from city i to city j
+//   min   sum d[i][j] a_i_j + sum matrix[i][k] x_i_k
+//   s.t.  every city has one position and every position one city (assignment)
+//         every city has one outgoing and one incoming arc
+//         pos(j) >= pos(i) + 1 - n (1 - a_i_j) - n x_j_0, where pos(i) = sum k x_i_k
+// The ordering constraints forbid subtours and, with the assignment, make the arcs follow
+// the positions: an arc i -> j means j is right after i, unless j is the first city
+
+enum Sense {
+    Equal,
+    GreaterEqual,
+}
+
+struct Row {
+    name: String,
+    sense: Sense,
+    rhs: i64,
+    terms: Vec<(usize, i64)>,
+}
+
+struct Model {
+    columns: Vec<String>,
+    objective: Vec<i64>,
+    rows: Vec<Row>,
+}
+
+impl Instance {
+    fn milp_model(&self) -> Model {
+        let size = self.size();
+        let n = size as i64;
+        let mut columns = vec![];
+        let mut objective = vec![];
+        let mut position = vec![vec![0; size]; size];
+        let mut arc = vec![vec![usize::MAX; size]; size];
+        for (i, city_columns) in position.iter_mut().enumerate() {
+            for (k, column) in city_columns.iter_mut().enumerate() {
+                *column = columns.len();
+                columns.push(format!("x_{}_{}", i, k));
+                objective.push(if self.matrix.is_empty() { 0 } else { self.matrix[i][k] });
+            }
+        }
+        for (i, out_columns) in arc.iter_mut().enumerate() {
+            for (j, column) in out_columns.iter_mut().enumerate() {
+                if i != j {
+                    *column = columns.len();
+                    columns.push(format!("a_{}_{}", i, j));
+                    objective.push(self.distance(i, j));
+                }
+            }
+        }
+
+        let mut rows = vec![];
+        for (i, city_columns) in position.iter().enumerate() {
+            rows.push(Row {
+                name: format!("city_{}", i),
+                sense: Sense::Equal,
+                rhs: 1,
+                terms: city_columns.iter().map(|&column| (column, 1)).collect(),
+            });
+        }
+        rows.extend((0..size).map(|k| Row {
+            name: format!("position_{}", k),
+            sense: Sense::Equal,
+            rhs: 1,
+            terms: position.iter().map(|city_columns| (city_columns[k], 1)).collect(),
+        }));
+        for (i, out_columns) in arc.iter().enumerate() {
+            rows.push(Row {
+                name: format!("out_{}", i),
+                sense: Sense::Equal,
+                rhs: 1,
+                terms: (0..size).filter(|&j| j != i).map(|j| (out_columns[j], 1)).collect(),
+            });
+            rows.push(Row {
+                name: format!("in_{}", i),
+                sense: Sense::Equal,
+                rhs: 1,
+                terms: (0..size).filter(|&j| j != i).map(|j| (arc[j][i], 1)).collect(),
+            });
+        }
+        for i in 0..size {
+            for j in 0..size {
+                if i == j {
+                    continue;
+                }
+                // pos(j) - pos(i) - n a_i_j + n x_j_0 >= 1 - n
+                let mut terms = vec![];
+                for (k, (&column_j, &column_i)) in position[j].iter().zip(&position[i]).enumerate().skip(1) {
+                    terms.push((column_j, k as i64));
+                    terms.push((column_i, -(k as i64)));
+                }
+                terms.push((arc[i][j], -n));
+                terms.push((position[j][0], n));
+                rows.push(Row { name: format!("order_{}_{}", i, j), sense: Sense::GreaterEqual, rhs: 1 - n, terms });
+            }
+        }
+
+        Model { columns, objective, rows }
+    }
+
+    // CPLEX LP format, read by CBC, HiGHS, GLPK and most other solvers
+    pub fn write_lp(&self, file_name: &str) {
+        let model = self.milp_model();
+        let file = File::create(file_name).expect("Falha ao criar o arquivo");
+        let mut out = BufWriter::new(file);
+        let write_terms = |out: &mut BufWriter<File>, terms: &Vec<(usize, i64)>| {
+            for (t, &(column, coefficient)) in terms.iter().enumerate() {
+                // LP files limit the line length, so long expressions are wrapped
+                if t > 0 && t % 8 == 0 {
+                    write!(out, "\n   ").unwrap();
+                }
+                let sign = if coefficient < 0 { "-" } else { "+" };
+                write!(out, " {} {} {}", sign, coefficient.abs(), model.columns[column]).unwrap();
+            }
+        };
+
+        writeln!(out, "\\ TSP com multas por posicao, {} cidades", self.size()).unwrap();
+        writeln!(out, "Minimize").unwrap();
+        write!(out, " obj:").unwrap();
+        let objective: Vec<(usize, i64)> = model.objective.iter().enumerate()
+            .filter(|(_, &c)| c != 0)
+            .map(|(column, &c)| (column, c))
+            .collect();
+        write_terms(&mut out, &objective);
+        writeln!(out).unwrap();
+        writeln!(out, "Subject To").unwrap();
+        for row in &model.rows {
+            write!(out, " {}:", row.name).unwrap();
+            write_terms(&mut out, &row.terms);
+            let sense = match row.sense {
+                Sense::Equal => "=",
+                Sense::GreaterEqual => ">=",
+            };
+            writeln!(out, " {} {}", sense, row.rhs).unwrap();
+        }
+        writeln!(out, "Binary").unwrap();
+        for (c, column) in model.columns.iter().enumerate() {
+            write!(out, " {}", column).unwrap();
+            if c % 8 == 7 {
+                writeln!(out).unwrap();
+            }
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "End").unwrap();
+    }
+
+    // Free MPS format
+    pub fn write_mps(&self, file_name: &str) {
+        let model = self.milp_model();
+        let file = File::create(file_name).expect("Falha ao criar o arquivo");
+        let mut out = BufWriter::new(file);
+
+        let mut column_rows: Vec<Vec<(usize, i64)>> = vec![vec![]; model.columns.len()];
+        for (r, row) in model.rows.iter().enumerate() {
+            for &(column, coefficient) in &row.terms {
+                column_rows[column].push((r, coefficient));
+            }
+        }
+
+        writeln!(out, "NAME tspp").unwrap();
+        writeln!(out, "ROWS").unwrap();
+        writeln!(out, " N obj").unwrap();
+        for row in &model.rows {
+            let sense = match row.sense {
+                Sense::Equal => "E",
+                Sense::GreaterEqual => "G",
+            };
+            writeln!(out, " {} {}", sense, row.name).unwrap();
+        }
+        writeln!(out, "COLUMNS").unwrap();
+        writeln!(out, " MARKER 'MARKER' 'INTORG'").unwrap();
+        for (c, column) in model.columns.iter().enumerate() {
+            if model.objective[c] != 0 {
+                writeln!(out, " {} obj {}", column, model.objective[c]).unwrap();
+            }
+            for &(r, coefficient) in &column_rows[c] {
+                writeln!(out, " {} {} {}", column, model.rows[r].name, coefficient).unwrap();
+            }
+        }
+        writeln!(out, " MARKER 'MARKER' 'INTEND'").unwrap();
+        writeln!(out, "RHS").unwrap();
+        for row in &model.rows {
+            if row.rhs != 0 {
+                writeln!(out, " rhs {} {}", row.name, row.rhs).unwrap();
+            }
+        }
+        writeln!(out, "BOUNDS").unwrap();
+        for column in &model.columns {
+            writeln!(out, " BV bnd {}", column).unwrap();
+        }
+        writeln!(out, "ENDATA").unwrap();
+    }
+
+    // Reads the x_i_k variables of a solution file (CBC, HiGHS, Gurobi and GLPK all write the
+    // variable name followed by its value on the same line) and returns the tour they encode
+    pub fn read_milp_solution(&self, file_name: &str) -> Vec<usize> {
+        let n = self.size();
+        let mut tour = vec![usize::MAX; n];
+        for line in read_file(file_name).lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            for (t, token) in tokens.iter().enumerate() {
+                let indices: Vec<usize> = match token.strip_prefix("x_") {
+                    Some(rest) => rest.split('_').filter_map(|i| i.parse().ok()).collect(),
+                    None => continue,
+                };
+                if indices.len() != 2 || indices[0] >= n || indices[1] >= n {
+                    continue;
+                }
+                let value = tokens[t+1..].iter().find_map(|v| v.parse::<f64>().ok());
+                if value.is_some_and(|v| v > 0.5) {
+                    tour[indices[1]] = indices[0];
+                }
+            }
+        }
+
+        if let Some(position) = tour.iter().position(|&city| city == usize::MAX) {
+            panic!("Solucao invalida no arquivo {}: posicao {} sem cidade", file_name, position);
+        }
+        if let Err(message) = self.validate(&tour) {
+            panic!("Solucao invalida no arquivo {}: {}", file_name, message);
+        }
+        tour
+    }
+}
@@ -0,0 +1,298 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+use crate::{derive_seed, read_file};
+use crate::aco::{Aco, AcoParams};
+use crate::checkpoint::Snapshot;
+use crate::solution::RunStats;
+use crate::solver::{Budget, Params, Registry, Solver};
+
+// Moving-target TSP: an agent leaves the first target and intercepts every other one, which
+// move in straight lines at constant speed, before returning to the first
+
+pub(crate) fn distance(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    let dx = x2 - x1;
+    let dy = y2 - y1;
+    (dx * dx + dy * dy).sqrt()
+}
+
+pub(crate) fn interception_point(ct: f64, target: &Target, agent_x: f64, agent_y: f64, agent_speed: f64) -> (f64, f64) {
+    let mut dist = f64::INFINITY;
+    let mut dt: f64;
+    let mut ds: f64;
+    let mut next_target_x = target.x + target.x_speed*ct;
+    let mut next_target_y = target.y + target.y_speed*ct;
+    let mut prev_target_x: f64;
+    let mut prev_target_y: f64;
+
+    while dist > 10e-4 {
+        ds = distance(agent_x, agent_y, next_target_x, next_target_y);
+        dt = ds/agent_speed;
+        prev_target_x = next_target_x;
+        prev_target_y = next_target_y;
+        next_target_x = target.x + target.x_speed*(ct + dt);
+        next_target_y = target.y + target.y_speed*(ct + dt);
+        dist = distance(prev_target_x, prev_target_y, next_target_x, next_target_y);
+    }
+    (next_target_x, next_target_y)
+}
+
+// Position at time 0 and velocity
+#[derive(Clone, Copy)]
+pub struct Target {
+    pub x: f64,
+    pub y: f64,
+    pub x_speed: f64,
+    pub y_speed: f64,
+}
+
+// A tour with its travelled distance and the stats of the run that found it
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub tour: Vec<usize>,
+    pub eval: i64,
+    pub stats: RunStats,
+}
+
+pub struct Instance {
+    pub targets: Vec<Target>,
+    pub agent_speed: f64,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Instance {
+    pub fn new() -> Self {
+        Self {
+            targets: Vec::new(),
+            agent_speed: 0.0,
+        }
+    }
+
+    pub fn set_data(&mut self, mttsp_file_name: &str) {
+        let content = read_file(mttsp_file_name);
+        let lines: Vec<Vec<&str>> = content.lines()
+            .map(|line| line.split_whitespace().collect()).collect();
+
+        self.agent_speed = lines[0][1].parse().unwrap_or(0.0);
+    
+        for line in lines.iter().skip(1) {
+            let xf: f64 = line[1].parse().unwrap_or(0.0);
+            let yf: f64 = line[2].parse().unwrap_or(0.0);
+            let x_s: f64 = line[3].parse().unwrap_or(0.0);
+            let y_s: f64 = line[4].parse().unwrap_or(0.0);
+    
+            let target = Target {
+                x: xf,
+                y: yf,
+                x_speed: x_s,
+                y_speed: y_s,
+            };
+            self.targets.push(target);
+        }
+    }
+
+    // Header with the number of targets and the agent speed, then "i x y x_speed y_speed"
+    pub fn write(&self, file_name: &str) {
+        let mut out = BufWriter::new(File::create(file_name).expect("Falha ao criar o arquivo"));
+        writeln!(out, "{} {}", self.targets.len(), self.agent_speed).unwrap();
+        for (i, target) in self.targets.iter().enumerate() {
+            writeln!(out, "{} {} {} {} {}", i + 1, target.x, target.y, target.x_speed, target.y_speed).unwrap();
+        }
+    }
+
+    pub fn evaluate(&self, solution: &[usize]) -> i64 {
+        let mut evaluation = 0.0;
+        let mut current_time = 0.0;
+        let mut agent_x = self.targets[solution[0]].x;
+        let mut agent_y = self.targets[solution[0]].y;
+        let mut travelled_distance: f64;
+        for &city in solution.iter().skip(1) {
+            let target = self.targets[city];
+            let interception_point = interception_point(
+                current_time, &target, agent_x, agent_y, self.agent_speed
+            );
+            travelled_distance = distance(agent_x, agent_y, interception_point.0, interception_point.1);
+            evaluation += travelled_distance;
+            current_time += travelled_distance/self.agent_speed;
+            agent_x = target.x + current_time*target.x_speed;
+            agent_y = target.y + current_time*target.y_speed;
+        }
+        let interception_point_origin = interception_point(
+            current_time, &self.targets[solution[0]], agent_x, agent_y, self.agent_speed
+        );
+        evaluation += distance(agent_x, agent_y, interception_point_origin.0, interception_point_origin.1);
+        evaluation.round() as i64
+    }
+
+    pub fn local_search(&self, init: &[usize]) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let mut solution = init.to_vec();
+        let mut better_option: Vec<usize> = vec![];
+        let mut eval_first = self.evaluate(&solution);
+        let mut eval_temp: i64;
+        let mut eval_better_option: i64 = i64::MAX;
+        let size = solution.len();
+        loop {
+            stats.iterations += 1;
+            for i in 1..size-1 {
+                for j in i+1..size {
+                    solution[i..=j].reverse();
+                    eval_temp = self.evaluate(&solution);
+                    stats.evaluations += 1;
+                    if eval_temp < eval_better_option {
+                        better_option = solution.clone();
+                        eval_better_option = eval_temp;
+                    }
+                    solution[i..=j].reverse();
+                }
+            }
+            if eval_better_option < eval_first {
+                solution = better_option;
+                better_option = vec![];
+                eval_first = eval_better_option;
+                eval_better_option = i64::MAX;
+            } else {
+                stats.time = start.elapsed();
+                return Solution { tour: solution, eval: eval_first, stats };
+            }
+        }
+    }
+
+    pub fn ils(&self, init: &[usize], params: &IlsParams, budget: &Budget, seed: u64) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let n_iter = budget.iterations_or(params.iterations as u64) as usize;
+        let snapshot = params.snapshot.as_deref();
+        let mut i: usize;
+        let mut j: usize;
+        let len = init.len();
+        let pert_strength = if init.len() > 30 {(init.len() as f64 * params.perturbation).ceil() as usize} else {3};
+        let mut solution = init.to_vec();
+        let mut eval_solution: i64;
+        let mut best_solution = solution.clone();
+        let mut first_iter = 0;
+        if let Some(saved) = snapshot.and_then(Snapshot::load) {
+            first_iter = saved.iteration;
+            solution = saved.tours[0].clone();
+            best_solution = saved.tours[1].clone();
+        }
+        let mut eval_best_solution = self.evaluate(&best_solution);
+
+        for k in first_iter..n_iter {
+            if budget.expired(start) {
+                break;
+            }
+            if let Some(path) = snapshot {
+                Snapshot { iteration: k, tours: vec![solution.clone(), best_solution.clone()], values: vec![] }.save(path);
+            }
+            stats.iterations += 1;
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, k as u64));
+            let local_optimum = self.local_search(&solution);
+            stats.include(&local_optimum.stats);
+            solution = local_optimum.tour;
+            eval_solution = local_optimum.eval;
+
+            if eval_best_solution > eval_solution {
+                best_solution = solution.clone();
+                eval_best_solution = eval_solution;
+            }
+            
+            for _ in 0..pert_strength {
+                i = rng.gen_range(1..len);
+                j = rng.gen_range(1..len);
+                while i == j {
+                    j = rng.gen_range(1..len);
+                }
+                let element = solution.remove(i);
+                solution.insert(j, element);
+            }
+        }
+        if let Some(path) = snapshot {
+            Snapshot::remove(path);
+        }
+        stats.time = start.elapsed();
+        Solution { tour: best_solution, eval: eval_best_solution, stats }
+    }
+
+}
+
+pub struct IlsParams {
+    pub iterations: usize,
+    // Random moves of each perturbation, as a fraction of the number of targets (3 up to 30 targets)
+    pub perturbation: f64,
+    pub snapshot: Option<String>,
+}
+
+impl IlsParams {
+    pub const NAMES: [&'static str; 3] = ["iterations", "perturbation", "snapshot"];
+
+    pub fn from_params(params: &Params) -> Self {
+        Self {
+            iterations: params.get_usize("iterations", 50),
+            perturbation: params.get("perturbation", 0.2),
+            snapshot: params.get_string("snapshot"),
+        }
+    }
+}
+
+// From the targets in file order
+struct Ils {
+    params: IlsParams,
+}
+
+impl Solver<Instance, Solution> for Ils {
+    fn solve(&self, instance: &Instance, rng: &mut StdRng, budget: &Budget) -> Solution {
+        instance.ils(&(0..instance.targets.len()).collect::<Vec<usize>>(), &self.params, budget, rng.gen())
+    }
+}
+
+pub fn registry() -> Registry<Instance, Solution> {
+    let mut registry = Registry::new();
+    registry.register("aco", |params| {
+        params.check("aco", &AcoParams::NAMES);
+        Box::new(Aco { params: AcoParams::from_params(params) })
+    });
+    registry.register("ils", |params| {
+        params.check("ils", &IlsParams::NAMES);
+        Box::new(Ils { params: IlsParams::from_params(params) })
+    });
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::solution::check_permutation;
+
+    #[test]
+    fn every_solver_returns_a_tour_on_a_zero_budget() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let targets = (0..10)
+            .map(|_| Target {
+                x: rng.gen_range(0.0..100.0),
+                y: rng.gen_range(0.0..100.0),
+                x_speed: rng.gen_range(-1.0..1.0),
+                y_speed: rng.gen_range(-1.0..1.0),
+            })
+            .collect();
+        let instance = Instance { targets, agent_speed: 10.0 };
+
+        let zero = Budget { iterations: Some(0), time: Some(Duration::ZERO) };
+        let registry = registry();
+        for name in registry.names() {
+            let solver = registry.build(name, &Params::default()).unwrap();
+            let solution = solver.solve(&instance, &mut StdRng::seed_from_u64(1), &zero);
+            assert_eq!(check_permutation(&solution.tour, instance.targets.len()), Ok(()), "{}", name);
+            assert_eq!(solution.eval, instance.evaluate(&solution.tour), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Instant;
+use crate::{Cost, Instance};
+use crate::solution::{RunStats, Solution};
+
+// Neighborhoods that keep the direction of every edge, so they are safe on asymmetric
+// instances. Both cut the tour into A B C D at positions i < j < k and reconnect it as
+// A C B D (Or-3opt): Or-opt when B or C has at most OR_OPT_MAX_SEGMENT cities, the
+// reversal-free 3-opt for any lengths
+
+const OR_OPT_MAX_SEGMENT: usize = 3;
+
+impl Instance {
+    // Cost change of A B C D -> A C B D, counting only the distances
+    fn exchange_delta(&self, solution: &[usize], i: usize, j: usize, k: usize) -> Cost {
+        let d = |from: usize, to: usize| self.distance(from, to);
+        let n = solution.len();
+        let (a, b_first, b_last) = (solution[i-1], solution[i], solution[j-1]);
+        let (c_first, c_last, after) = (solution[j], solution[k-1], solution[k % n]);
+        d(a, c_first) + d(c_last, b_first) + d(b_last, after)
+            - d(a, b_first) - d(b_last, c_first) - d(c_last, after)
+    }
+
+    fn exchanged(&self, solution: &[usize], i: usize, j: usize, k: usize) -> Vec<usize> {
+        let mut neighbor = solution[..i].to_vec();
+        neighbor.extend_from_slice(&solution[j..k]);
+        neighbor.extend_from_slice(&solution[i..j]);
+        neighbor.extend_from_slice(&solution[k..]);
+        neighbor
+    }
+
+    // Best improvement over the exchanges where B or C has at most `max_segment` cities,
+    // repeated until none improves. With penalties every position after i moves, so
+    // candidates are evaluated in full
+    fn segment_search(&self, init: &[usize], max_segment: usize) -> Solution {
+        let start = Instant::now();
+        let mut stats = RunStats { evaluations: 1, ..RunStats::default() };
+        let mut solution = init.to_vec();
+        let n = solution.len();
+        let has_penalty = !self.matrix.is_empty();
+        let mut eval_solution = self.evaluate(&solution);
+        loop {
+            stats.iterations += 1;
+            let mut best = (0, 0, 0, 0);
+            for i in 1..n {
+                for j in i+1..n {
+                    let k_max = if j - i <= max_segment { n } else { j.saturating_add(max_segment).min(n) };
+                    for k in j+1..=k_max {
+                        stats.evaluations += 1;
+                        let delta = if has_penalty {
+                            self.evaluate(&self.exchanged(&solution, i, j, k)) - eval_solution
+                        } else {
+                            self.exchange_delta(&solution, i, j, k)
+                        };
+                        if delta < best.0 {
+                            best = (delta, i, j, k);
+                        }
+                    }
+                }
+            }
+            if best.0 >= 0 {
+                stats.time = start.elapsed();
+                return Solution { stats, ..self.solution(solution) };
+            }
+            let (delta, i, j, k) = best;
+            solution = self.exchanged(&solution, i, j, k);
+            eval_solution += delta;
+        }
+    }
+
+    pub fn or_opt(&self, init: &[usize]) -> Solution {
+        self.segment_search(init, OR_OPT_MAX_SEGMENT)
+    }
+
+    // O(n^3) per pass: used without penalties only, where the deltas are O(1)
+    pub fn segment_exchange(&self, init: &[usize]) -> Solution {
+        self.segment_search(init, usize::MAX)
+    }
+}
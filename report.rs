@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use crate::read_file;
+use crate::exact::Proof;
+use crate::solver::Params;
+
+// How the programs print the result of a run
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Format {
+        match name {
+            "text" => Format::Text,
+            "json" => Format::Json,
+            _ => panic!("Formato desconhecido: {}", name),
+        }
+    }
+}
+
+// Result of a run as printed by --format json. Costs are in the units of the instance and the
+// time in seconds. The lower bound and the gap to it (in percent) are only present when asked
+// for, and the proof of optimality only for the exact solver
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SolveReport {
+    pub instance: String,
+    pub solver: String,
+    pub params: Params,
+    pub seed: u64,
+    pub tour: Vec<usize>,
+    pub cost: f64,
+    pub distance: f64,
+    pub penalty: f64,
+    pub time: f64,
+    pub iterations: Option<u64>,
+    pub evaluations: Option<u64>,
+    pub bound: Option<f64>,
+    pub gap: Option<f64>,
+    pub proof: Option<Proof>,
+}
+
+impl SolveReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Falha ao gerar o JSON")
+    }
+
+    pub fn from_json(text: &str) -> Result<SolveReport, String> {
+        serde_json::from_str(text).map_err(|error| error.to_string())
+    }
+
+    pub fn read(file_name: &str) -> SolveReport {
+        SolveReport::from_json(&read_file(file_name)).unwrap_or_else(|message| panic!("Resultado invalido: {}", message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SolveReport {
+        SolveReport {
+            instance: "t30.tspp".to_string(),
+            solver: "exact".to_string(),
+            params: Params::parse("incumbent-iterations=10"),
+            seed: 3,
+            tour: vec![0, 2, 1],
+            cost: 12.5,
+            distance: 10.0,
+            penalty: 2.5,
+            time: 0.25,
+            iterations: Some(7),
+            evaluations: Some(40),
+            bound: Some(11.0),
+            gap: Some(100.0 * 1.5 / 11.0),
+            proof: Some(Proof::BranchAndBound { nodes: 5, root_bound: 11 }),
+        }
+    }
+
+    #[test]
+    fn json_has_every_field() {
+        let value: serde_json::Value = serde_json::from_str(&sample().to_json()).unwrap();
+        let keys: Vec<&str> = value.as_object().unwrap().keys().map(|key| key.as_str()).collect();
+        for field in ["instance", "solver", "params", "seed", "tour", "cost", "distance", "penalty", "time",
+                      "iterations", "evaluations", "bound", "gap", "proof"] {
+            assert!(keys.contains(&field), "{}", field);
+        }
+        assert_eq!(value["params"]["incumbent-iterations"], "10");
+        assert_eq!(value["tour"], serde_json::json!([0, 2, 1]));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let report = sample();
+        let read = SolveReport::from_json(&report.to_json()).unwrap();
+        assert_eq!(read.to_json(), report.to_json());
+        assert_eq!(read.proof, report.proof);
+        assert_eq!(read.params.get_string("incumbent-iterations").as_deref(), Some("10"));
+    }
+
+    #[test]
+    fn optional_fields_may_be_missing() {
+        let text = r#"{"instance": "m20.mttsp", "solver": "aco", "params": {}, "seed": 1, "tour": [1, 0],
+            "cost": 3.0, "distance": 3.0, "penalty": 0.0, "time": 0.1}"#;
+        let read = SolveReport::from_json(text).unwrap();
+        assert!(read.iterations.is_none() && read.bound.is_none() && read.proof.is_none());
+    }
+}
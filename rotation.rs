@@ -0,0 +1,74 @@
+use crate::Instance;
+
+impl Instance {
+    // The distance of a cycle does not depend on which city is at index 0 or (on symmetric
+    // instances) on the direction, but the penalty matrix[solution[k]][k] does. The penalties of
+    // all n rotations in both directions are accumulated in a single O(n^2) pass over the matrix,
+    // and the best is applied
+    pub fn best_rotation(&self, solution: &[usize]) -> Vec<usize> {
+        let n = solution.len();
+        if self.matrix.is_empty() || n < 2 {
+            return solution.to_vec();
+        }
+
+        // forward[r]: penalty of solution[r], solution[r+1], ...
+        // backward[r]: penalty of solution[r], solution[r-1], ...
+        let mut forward = vec![0i64; n];
+        let mut backward = vec![0i64; n];
+        for j in 0..n {
+            let row = &self.matrix[solution[j]];
+            for k in 0..n {
+                let penalty = row[k];
+                forward[(j + n - k) % n] += penalty;
+                backward[(j + k) % n] += penalty;
+            }
+        }
+
+        let mut best = (forward[0], 0, false);
+        for r in 0..n {
+            if forward[r] < best.0 {
+                best = (forward[r], r, false);
+            }
+            if self.symmetric && backward[r] < best.0 {
+                best = (backward[r], r, true);
+            }
+        }
+
+        let (_, r, reversed) = best;
+        (0..n).map(|k| if reversed { solution[(r + n - k) % n] } else { solution[(r + k) % n] }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Instance;
+    use rand::{Rng, SeedableRng};
+    use rand::seq::SliceRandom;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn best_rotation_is_the_best_of_every_rotation_and_direction() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for trial in 0..100 {
+            let n = 2 + trial % 9;
+            let symmetric = trial % 2 == 0;
+            let mut instance = Instance::new();
+            instance.symmetric = symmetric;
+            instance.matrix = (0..n).map(|_| (0..n).map(|_| rng.gen_range(0..100)).collect()).collect();
+
+            let mut solution: Vec<usize> = (0..n).collect();
+            solution.shuffle(&mut rng);
+            let mut candidates: Vec<Vec<usize>> = (0..n)
+                .map(|r| (0..n).map(|k| solution[(r + k) % n]).collect())
+                .collect();
+            if symmetric {
+                candidates.extend((0..n).map(|r| (0..n).map(|k| solution[(r + n - k) % n]).collect()));
+            }
+            let best = candidates.iter().map(|tour| instance.tour_penalty(tour)).min().unwrap();
+
+            let rotated = instance.best_rotation(&solution);
+            assert!(candidates.contains(&rotated));
+            assert_eq!(instance.tour_penalty(&rotated), best);
+        }
+    }
+}
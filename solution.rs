@@ -0,0 +1,99 @@
+use std::time::Duration;
+use crate::{Cost, Instance};
+use crate::exact::Proof;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunStats {
+    // Iterations of the solver's main loop (2-opt passes, SA moves, ILS iterations...) plus
+    // those of the searches it called
+    pub iterations: u64,
+    // Tours and moves whose cost was computed, in full or by delta, including those of the
+    // searches the solver called
+    pub evaluations: u64,
+    pub time: Duration,
+    // Set by the exact solvers, whose tour is optimal
+    pub proof: Option<Proof>,
+}
+
+impl RunStats {
+    // Counts the iterations and evaluations of a search run inside this one
+    pub fn include(&mut self, inner: &RunStats) {
+        self.iterations += inner.iterations;
+        self.evaluations += inner.evaluations;
+    }
+}
+
+// A tour with its cost, cached when it is built, and the stats of the run that found it
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub tour: Vec<usize>,
+    pub distance: Cost,
+    pub penalty: Cost,
+    pub stats: RunStats,
+}
+
+impl Solution {
+    pub fn cost(&self) -> Cost {
+        self.distance + self.penalty
+    }
+}
+
+impl Instance {
+    pub fn solution(&self, tour: Vec<usize>) -> Solution {
+        let (distance, penalty) = (self.tour_distance(&tour), self.tour_penalty(&tour));
+        Solution { tour, distance, penalty, stats: RunStats { evaluations: 1, ..RunStats::default() } }
+    }
+
+    // Length of the cycle, closing edge included
+    pub fn tour_distance(&self, tour: &[usize]) -> Cost {
+        let n = tour.len();
+        (0..n).map(|k| self.distance(tour[k], tour[(k + 1) % n])).sum()
+    }
+
+    // Penalty of visiting tour[k] at position k, for every k
+    pub fn tour_penalty(&self, tour: &[usize]) -> Cost {
+        if self.matrix.is_empty() {
+            return 0;
+        }
+        tour.iter().enumerate().map(|(k, &city)| self.matrix[city][k]).sum()
+    }
+
+    // Whether the tour visits every city of the instance exactly once
+    pub fn validate(&self, tour: &Vec<usize>) -> Result<(), String> {
+        check_permutation(tour, self.size())
+    }
+}
+
+// Whether the tour is a permutation of 0..n
+pub fn check_permutation(tour: &Vec<usize>, n: usize) -> Result<(), String> {
+    if tour.len() != n {
+        return Err(format!("{} cidades em vez de {}", tour.len(), n));
+    }
+    let mut seen = vec![false; n];
+    for &city in tour {
+        if city >= n {
+            return Err(format!("cidade {} inexistente", city));
+        }
+        if seen[city] {
+            return Err(format!("cidade {} repetida", city));
+        }
+        seen[city] = true;
+    }
+    Ok(())
+}
+
+// A TSPLIB tour file (1-based, ended by -1), or the cities from 0 in any other text, such as
+// the printed tour "[3, 0, 2, 1]"
+pub fn read_tour(content: &str) -> Vec<usize> {
+    if let Some((_, section)) = content.split_once("TOUR_SECTION") {
+        return section.split_whitespace()
+            .map(|t| t.parse::<i64>().expect("Cidade invalida no tour"))
+            .take_while(|&city| city >= 0)
+            .map(|city| (city - 1) as usize)
+            .collect();
+    }
+    content.split(|c: char| !c.is_ascii_digit())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse().expect("Cidade invalida no tour"))
+        .collect()
+}
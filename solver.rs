@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+// Limits of a run. Solvers stop at whichever is reached first; without an iteration limit
+// they run their default number of iterations
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Budget {
+    pub iterations: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn iterations_or(&self, default: u64) -> u64 {
+        self.iterations.unwrap_or(default)
+    }
+
+    pub fn expired(&self, start: Instant) -> bool {
+        self.time.is_some_and(|limit| start.elapsed() >= limit)
+    }
+}
+
+// Parameters by name, as given on the command line ("temp=5000,alfa=0.999", commas or spaces
+// between pairs) or by the tuner. Serialized as a map from name to value
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Params {
+    values: HashMap<String, String>,
+}
+
+impl Params {
+    pub fn parse(text: &str) -> Params {
+        let mut params = Params::default();
+        for pair in text.split(|c: char| c == ',' || c.is_whitespace()).filter(|pair| !pair.is_empty()) {
+            match pair.split_once('=') {
+                Some((name, value)) => params.set(name, value),
+                None => panic!("Parametro sem valor: {}", pair),
+            }
+        }
+        params
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str, default: T) -> T {
+        match self.values.get(name) {
+            Some(value) => value.parse().unwrap_or_else(|_| panic!("Valor invalido para o parametro {}: {}", name, value)),
+            None => default,
+        }
+    }
+
+    // Integer parameters coming from the tuner are reals
+    pub fn get_usize(&self, name: &str, default: usize) -> usize {
+        self.get(name, default as f64).round() as usize
+    }
+
+    pub fn get_string(&self, name: &str) -> Option<String> {
+        self.values.get(name).cloned()
+    }
+
+    // Catches misspelled names, which would otherwise silently keep the default
+    pub fn check(&self, solver: &str, known: &[&str]) {
+        for name in self.values.keys() {
+            if !known.contains(&name.as_str()) {
+                panic!("Parametro desconhecido para {}: {} (aceitos: {})", solver, name, known.join(", "));
+            }
+        }
+    }
+}
+
+// Solvers print nothing; the front-ends report the result
+pub trait Solver<I, S>: Sync {
+    fn solve(&self, instance: &I, rng: &mut StdRng, budget: &Budget) -> S;
+}
+
+type Builder<I, S> = fn(&Params) -> Box<dyn Solver<I, S>>;
+
+// Solvers by name, each built from its parameters
+pub struct Registry<I, S> {
+    builders: Vec<(&'static str, Builder<I, S>)>,
+}
+
+impl<I, S> Default for Registry<I, S> {
+    fn default() -> Self {
+        Registry { builders: vec![] }
+    }
+}
+
+impl<I, S> Registry<I, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &'static str, build: Builder<I, S>) {
+        self.builders.push((name, build));
+    }
+
+    pub fn build(&self, name: &str, params: &Params) -> Option<Box<dyn Solver<I, S>>> {
+        self.builders.iter()
+            .find(|(registered, _)| registered.eq_ignore_ascii_case(name))
+            .map(|(_, build)| build(params))
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.builders.iter().map(|(name, _)| *name).collect()
+    }
+}
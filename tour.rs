@@ -0,0 +1,422 @@
+// Tours as cyclic sequences of cities. Local searches move through them with next/prev and
+// apply 2-opt moves with flip, so the cost of a move depends on the representation: O(n) for
+// an array, O(sqrt n) for a two-level list
+
+// Tours with at least this many cities use the two-level list; below it the array flips faster
+pub const TWO_LEVEL_MIN_CITIES: usize = 5000;
+
+pub trait Tour {
+    fn new(solution: &[usize]) -> Self;
+    fn size(&self) -> usize;
+    fn next(&self, city: usize) -> usize;
+    fn prev(&self, city: usize) -> usize;
+    // Whether b is on the path that goes forward from a to c, both included
+    fn between(&self, a: usize, b: usize, c: usize) -> bool;
+    // Reverses the path that goes forward from a to b, both included
+    fn flip(&mut self, a: usize, b: usize);
+    // The city k steps forward from `city`
+    fn nth(&self, city: usize, k: usize) -> usize;
+
+    // The tour as a sequence starting at `first`
+    fn order_from(&self, first: usize) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.size());
+        let mut city = first;
+        for _ in 0..self.size() {
+            order.push(city);
+            city = self.next(city);
+        }
+        order
+    }
+}
+
+// Cities in an array with the position of each. Flips reverse the shorter of the path and its
+// complement; reversing the complement leaves the tour backwards, which `reversed` records
+pub struct ArrayTour {
+    order: Vec<usize>,
+    position: Vec<usize>,
+    reversed: bool,
+}
+
+impl ArrayTour {
+    fn after(&self, p: usize) -> usize {
+        if p + 1 == self.order.len() { 0 } else { p + 1 }
+    }
+
+    fn before(&self, p: usize) -> usize {
+        if p == 0 { self.order.len() - 1 } else { p - 1 }
+    }
+
+    // Reverses the `len` cities of `order` from position `start` on, wrapping around the end
+    fn reverse(&mut self, start: usize, len: usize) {
+        let n = self.order.len();
+        for t in 0..len / 2 {
+            let (p, q) = ((start + t) % n, (start + len - 1 - t) % n);
+            self.order.swap(p, q);
+            self.position[self.order[p]] = p;
+            self.position[self.order[q]] = q;
+        }
+    }
+}
+
+impl Tour for ArrayTour {
+    fn new(solution: &[usize]) -> Self {
+        let mut position = vec![0; solution.len()];
+        for (p, &city) in solution.iter().enumerate() {
+            position[city] = p;
+        }
+        ArrayTour { order: solution.to_vec(), position, reversed: false }
+    }
+
+    fn size(&self) -> usize {
+        self.order.len()
+    }
+
+    fn next(&self, city: usize) -> usize {
+        if self.reversed { self.order[self.before(self.position[city])] } else { self.order[self.after(self.position[city])] }
+    }
+
+    fn prev(&self, city: usize) -> usize {
+        if self.reversed { self.order[self.after(self.position[city])] } else { self.order[self.before(self.position[city])] }
+    }
+
+    fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        let n = self.size();
+        let (a, c) = if self.reversed { (c, a) } else { (a, c) };
+        let (pa, pb, pc) = (self.position[a], self.position[b], self.position[c]);
+        (pb + n - pa) % n <= (pc + n - pa) % n
+    }
+
+    fn flip(&mut self, a: usize, b: usize) {
+        let n = self.size();
+        let (a, b) = if self.reversed { (b, a) } else { (a, b) };
+        let len = (self.position[b] + n - self.position[a]) % n + 1;
+        if 2 * len <= n {
+            self.reverse(self.position[a], len);
+        } else {
+            self.reverse((self.position[b] + 1) % n, n - len);
+            self.reversed = !self.reversed;
+        }
+    }
+
+    fn nth(&self, city: usize, k: usize) -> usize {
+        let n = self.size();
+        let k = k % n;
+        let p = self.position[city];
+        if self.reversed { self.order[(p + n - k) % n] } else { self.order[(p + k) % n] }
+    }
+}
+
+struct Segment {
+    cities: Vec<usize>,
+    // The cities are traversed from the last to the first
+    reversed: bool,
+}
+
+impl Segment {
+    // The city `offset` steps into the segment in traversal order
+    fn at(&self, offset: usize) -> usize {
+        if self.reversed { self.cities[self.cities.len() - 1 - offset] } else { self.cities[offset] }
+    }
+}
+
+// Two-level list (Fredman, Johnson, McGeoch and Ostheimer, 1995): the tour is cut into about
+// sqrt(n) segments, each an array with a reversal bit, kept in tour order by the top level.
+// A flip splits the segments at the ends of the path and then reverses the order and the bits
+// of the O(sqrt n) segments in between; short paths inside one segment are reversed in place
+pub struct TwoLevelTour {
+    segments: Vec<Segment>,
+    // Segment ids in tour order, and the index of each segment in it
+    order: Vec<usize>,
+    rank: Vec<usize>,
+    // Segment of each city and its index in that segment's array
+    segment: Vec<usize>,
+    index: Vec<usize>,
+    // Ids of emptied segments, reused by splits
+    free: Vec<usize>,
+    group_size: usize,
+    reversed: bool,
+}
+
+impl TwoLevelTour {
+    // Cuts `solution` into segments of `group_size` cities
+    fn rebuild(&mut self, solution: &[usize]) {
+        self.segments = solution.chunks(self.group_size)
+            .map(|chunk| Segment { cities: chunk.to_vec(), reversed: false })
+            .collect();
+        self.order = (0..self.segments.len()).collect();
+        self.rank = self.order.clone();
+        self.free.clear();
+        for s in 0..self.segments.len() {
+            self.relabel(s, 0);
+        }
+    }
+
+    // Updates segment and index of the cities of segment s from index `from` on
+    fn relabel(&mut self, s: usize, from: usize) {
+        for (i, &city) in self.segments[s].cities.iter().enumerate().skip(from) {
+            self.segment[city] = s;
+            self.index[city] = i;
+        }
+    }
+
+    // Position of the city inside its segment, in traversal order
+    fn offset(&self, city: usize) -> usize {
+        let segment = &self.segments[self.segment[city]];
+        if segment.reversed { segment.cities.len() - 1 - self.index[city] } else { self.index[city] }
+    }
+
+    fn next_segment(&self, s: usize) -> usize {
+        self.order[(self.rank[s] + 1) % self.order.len()]
+    }
+
+    fn prev_segment(&self, s: usize) -> usize {
+        self.order[(self.rank[s] + self.order.len() - 1) % self.order.len()]
+    }
+
+    // Successor ignoring the orientation of the whole tour
+    fn raw_next(&self, city: usize) -> usize {
+        let s = self.segment[city];
+        let offset = self.offset(city);
+        if offset + 1 < self.segments[s].cities.len() {
+            self.segments[s].at(offset + 1)
+        } else {
+            self.segments[self.next_segment(s)].at(0)
+        }
+    }
+
+    fn raw_prev(&self, city: usize) -> usize {
+        let s = self.segment[city];
+        let offset = self.offset(city);
+        if offset > 0 {
+            self.segments[s].at(offset - 1)
+        } else {
+            let previous = &self.segments[self.prev_segment(s)];
+            previous.at(previous.cities.len() - 1)
+        }
+    }
+
+    fn raw_forward(&self, city: usize, k: usize) -> usize {
+        let mut s = self.segment[city];
+        let mut k = k + self.offset(city);
+        while k >= self.segments[s].cities.len() {
+            k -= self.segments[s].cities.len();
+            s = self.next_segment(s);
+        }
+        self.segments[s].at(k)
+    }
+
+    fn raw_backward(&self, city: usize, k: usize) -> usize {
+        let mut s = self.segment[city];
+        let mut k = k + self.segments[s].cities.len() - 1 - self.offset(city);
+        while k >= self.segments[s].cities.len() {
+            k -= self.segments[s].cities.len();
+            s = self.prev_segment(s);
+        }
+        self.segments[s].at(self.segments[s].cities.len() - 1 - k)
+    }
+
+    // Makes `city` the first of its segment, moving the cities from it on to a new segment
+    // placed right after
+    fn split_before(&mut self, city: usize) {
+        let s = self.segment[city];
+        let offset = self.offset(city);
+        if offset == 0 {
+            return;
+        }
+        let i = self.index[city];
+        let reversed = self.segments[s].reversed;
+        // In array order the segment is [kept, moved] when traversed forward, [moved, kept]
+        // when traversed backwards
+        let moved = if reversed {
+            let kept = self.segments[s].cities.split_off(i + 1);
+            std::mem::replace(&mut self.segments[s].cities, kept)
+        } else {
+            self.segments[s].cities.split_off(i)
+        };
+        let t = match self.free.pop() {
+            Some(t) => {
+                self.segments[t] = Segment { cities: moved, reversed };
+                t
+            },
+            None => {
+                self.segments.push(Segment { cities: moved, reversed });
+                self.rank.push(0);
+                self.segments.len() - 1
+            },
+        };
+        self.relabel(s, 0);
+        self.relabel(t, 0);
+        self.order.insert(self.rank[s] + 1, t);
+        self.rerank(self.rank[s] + 1);
+    }
+
+    fn rerank(&mut self, from: usize) {
+        for r in from..self.order.len() {
+            self.rank[self.order[r]] = r;
+        }
+    }
+
+    // Reverses the order and the bits of the `count` segments from rank `first` on, wrapping
+    // around the end of the top level
+    fn reverse_segments(&mut self, first: usize, count: usize) {
+        let m = self.order.len();
+        for t in 0..count / 2 {
+            self.order.swap((first + t) % m, (first + count - 1 - t) % m);
+        }
+        for t in 0..count {
+            let s = self.order[(first + t) % m];
+            self.rank[s] = (first + t) % m;
+            self.segments[s].reversed = !self.segments[s].reversed;
+        }
+    }
+
+    // Joins segment s with the next one when both fit in a group
+    fn merge_with_next(&mut self, s: usize) {
+        let t = self.next_segment(s);
+        if s == t || self.segments[s].cities.len() + self.segments[t].cities.len() > self.group_size {
+            return;
+        }
+        let mut cities: Vec<usize> = (0..self.segments[s].cities.len()).map(|k| self.segments[s].at(k)).collect();
+        cities.extend((0..self.segments[t].cities.len()).map(|k| self.segments[t].at(k)));
+        self.segments[s] = Segment { cities, reversed: false };
+        self.segments[t].cities.clear();
+        self.relabel(s, 0);
+        let r = self.rank[t];
+        self.order.remove(r);
+        self.rerank(r);
+        self.free.push(t);
+    }
+
+    fn raw_flip(&mut self, a: usize, b: usize) {
+        let n = self.segment.len();
+        if self.raw_next(b) == a {
+            // The whole tour: the same cycle read backwards
+            self.reversed = !self.reversed;
+            return;
+        }
+        let s = self.segment[a];
+        if s == self.segment[b] && self.offset(a) <= self.offset(b) {
+            // Inside one segment: reversing the array range is as cheap as splitting it
+            let (i, j) = (self.index[a].min(self.index[b]), self.index[a].max(self.index[b]));
+            self.segments[s].cities[i..=j].reverse();
+            self.relabel(s, i);
+            return;
+        }
+
+        self.split_before(a);
+        let after = self.raw_next(b);
+        self.split_before(after);
+        let (first, last) = (self.rank[self.segment[a]], self.rank[self.segment[b]]);
+        let m = self.order.len();
+        let count = (last + m - first) % m + 1;
+        if 2 * count <= m {
+            self.reverse_segments(first, count);
+        } else {
+            self.reverse_segments((last + 1) % m, m - count);
+            self.reversed = !self.reversed;
+        }
+
+        // Splits leave short segments at both ends of the path
+        for city in [a, b] {
+            let s = self.segment[city];
+            let previous = self.prev_segment(s);
+            self.merge_with_next(previous);
+            let s = self.segment[city];
+            self.merge_with_next(s);
+        }
+        if self.order.len() > 4 * n.div_ceil(self.group_size) {
+            let first = self.segments[self.order[0]].at(0);
+            let solution: Vec<usize> = (0..n).scan(first, |city, _| {
+                let current = *city;
+                *city = self.raw_next(current);
+                Some(current)
+            }).collect();
+            self.rebuild(&solution);
+        }
+    }
+}
+
+impl Tour for TwoLevelTour {
+    fn new(solution: &[usize]) -> Self {
+        let n = solution.len();
+        let group_size = ((n as f64).sqrt().ceil() as usize).max(1);
+        let mut tour = TwoLevelTour {
+            segments: vec![],
+            order: vec![],
+            rank: vec![],
+            segment: vec![0; n],
+            index: vec![0; n],
+            free: vec![],
+            group_size,
+            reversed: false,
+        };
+        tour.rebuild(solution);
+        tour
+    }
+
+    fn size(&self) -> usize {
+        self.segment.len()
+    }
+
+    fn next(&self, city: usize) -> usize {
+        if self.reversed { self.raw_prev(city) } else { self.raw_next(city) }
+    }
+
+    fn prev(&self, city: usize) -> usize {
+        if self.reversed { self.raw_next(city) } else { self.raw_prev(city) }
+    }
+
+    fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        let (a, c) = if self.reversed { (c, a) } else { (a, c) };
+        let sequence = |city: usize| (self.rank[self.segment[city]], self.offset(city));
+        let (sa, sb, sc) = (sequence(a), sequence(b), sequence(c));
+        if sa <= sc { sa <= sb && sb <= sc } else { sa <= sb || sb <= sc }
+    }
+
+    fn flip(&mut self, a: usize, b: usize) {
+        if self.reversed { self.raw_flip(b, a) } else { self.raw_flip(a, b) }
+    }
+
+    fn nth(&self, city: usize, k: usize) -> usize {
+        let k = k % self.size();
+        if self.reversed { self.raw_backward(city, k) } else { self.raw_forward(city, k) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::seq::SliceRandom;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn two_level_tour_matches_array_tour() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for n in [1, 2, 3, 5, 10, 17, 50, 101] {
+            let mut solution: Vec<usize> = (0..n).collect();
+            solution.shuffle(&mut rng);
+            let mut array = ArrayTour::new(&solution);
+            let mut two_level = TwoLevelTour::new(&solution);
+
+            for _ in 0..500 {
+                let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                array.flip(a, b);
+                two_level.flip(a, b);
+
+                let first = rng.gen_range(0..n);
+                assert_eq!(two_level.order_from(first), array.order_from(first));
+                for city in 0..n {
+                    assert_eq!(two_level.next(city), array.next(city));
+                    assert_eq!(two_level.prev(city), array.prev(city));
+                }
+                for _ in 0..10 {
+                    let (a, b, c) = (rng.gen_range(0..n), rng.gen_range(0..n), rng.gen_range(0..n));
+                    assert_eq!(two_level.between(a, b, c), array.between(a, b, c));
+                    let k = rng.gen_range(0..2 * n);
+                    assert_eq!(two_level.nth(a, k), array.nth(a, k));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::{City, Cost, Instance};
+use crate::metrics::Metric;
+
+// Reader for the TSPLIB format (Reinelt, 1991). Supports TSP and ATSP instances given by
+// coordinates (EUC_2D, MAN_2D, MAX_2D, GEO) or by explicit weights (FULL_MATRIX and the
+// triangular formats). The EDGE_WEIGHT_TYPE of the file takes precedence over --metric
+impl Instance {
+    pub fn read_tsplib(&mut self, content: &str) {
+        let mut dimension = 0;
+        let mut edge_weight_type: Option<String> = None;
+        let mut edge_weight_format = "FULL_MATRIX".to_string();
+        let mut weights: Vec<Cost> = vec![];
+        let mut lines = content.lines();
+
+        while let Some(line) = lines.next() {
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (line.trim(), ""),
+            };
+            match key {
+                "DIMENSION" => dimension = value.parse().expect("Dimensao invalida"),
+                "EDGE_WEIGHT_TYPE" => edge_weight_type = Some(value.to_string()),
+                "EDGE_WEIGHT_FORMAT" => edge_weight_format = value.to_string(),
+                "NODE_COORD_SECTION" => {
+                    for _ in 0..dimension {
+                        let tokens: Vec<f64> = lines.next().expect("Coordenadas incompletas")
+                            .split_whitespace()
+                            .map(|t| t.parse().expect("Coordenada invalida"))
+                            .collect();
+                        self.cities.push(City { x: tokens[1], y: tokens[2] });
+                    }
+                },
+                "EDGE_WEIGHT_SECTION" => {
+                    let count = match edge_weight_format.as_str() {
+                        "FULL_MATRIX" => dimension * dimension,
+                        "UPPER_ROW" | "LOWER_ROW" => dimension * (dimension - 1) / 2,
+                        "UPPER_DIAG_ROW" | "LOWER_DIAG_ROW" => dimension * (dimension + 1) / 2,
+                        _ => panic!("Formato de pesos nao suportado: {}", edge_weight_format),
+                    };
+                    while weights.len() < count {
+                        let line = lines.next().expect("Pesos incompletos");
+                        weights.extend(line.split_whitespace()
+                            .map(|t| (t.parse::<f64>().expect("Peso invalido") * self.scale as f64).round() as Cost));
+                    }
+                },
+                "EOF" => break,
+                _ => {},
+            }
+        }
+
+        match edge_weight_type.as_deref() {
+            Some("EXPLICIT") => {
+                self.set_distance_matrix(explicit_distances(dimension, &edge_weight_format, &weights));
+                return;
+            },
+            Some("EUC_2D") => self.metric = Metric::Euclidean,
+            Some("MAN_2D") => self.metric = Metric::Manhattan,
+            Some("MAX_2D") => self.metric = Metric::Chebyshev,
+            Some("GEO") => self.metric = Metric::Geo,
+            Some(other) => panic!("Tipo de distancia nao suportado: {}", other),
+            None => {},
+        }
+        self.compute_distances();
+    }
+}
+
+impl Instance {
+    // Coordinates when TSPLIB has their metric, the full matrix otherwise (ATSP, explicit
+    // instances, haversine distances, fixed point costs)
+    pub fn write_tsplib(&self, file_name: &str, name: &str) {
+        let mut out = BufWriter::new(File::create(file_name).expect("Falha ao criar o arquivo"));
+        let n = self.size();
+        let edge_weight_type = match self.metric {
+            _ if self.cities.len() != n || self.scale != 1 => None,
+            Metric::Euclidean => Some("EUC_2D"),
+            Metric::Manhattan => Some("MAN_2D"),
+            Metric::Chebyshev => Some("MAX_2D"),
+            Metric::Geo => Some("GEO"),
+            Metric::Haversine => None,
+        };
+        writeln!(out, "NAME: {}", name).unwrap();
+        writeln!(out, "TYPE: {}", if self.symmetric { "TSP" } else { "ATSP" }).unwrap();
+        writeln!(out, "DIMENSION: {}", n).unwrap();
+        match edge_weight_type {
+            Some(edge_weight_type) => {
+                writeln!(out, "EDGE_WEIGHT_TYPE: {}", edge_weight_type).unwrap();
+                writeln!(out, "NODE_COORD_SECTION").unwrap();
+                for (i, city) in self.cities.iter().enumerate() {
+                    writeln!(out, "{} {} {}", i + 1, city.x, city.y).unwrap();
+                }
+            },
+            None => {
+                writeln!(out, "EDGE_WEIGHT_TYPE: EXPLICIT").unwrap();
+                writeln!(out, "EDGE_WEIGHT_FORMAT: FULL_MATRIX").unwrap();
+                writeln!(out, "EDGE_WEIGHT_SECTION").unwrap();
+                for i in 0..n {
+                    let row: Vec<String> = (0..n).map(|j| self.format_cost(self.distance(i, j))).collect();
+                    writeln!(out, "{}", row.join(" ")).unwrap();
+                }
+            },
+        }
+        writeln!(out, "EOF").unwrap();
+    }
+}
+
+// TSPLIB tour file, read back by solution::read_tour
+pub fn write_tour(file_name: &str, name: &str, tour: &Vec<usize>) {
+    let mut out = BufWriter::new(File::create(file_name).expect("Falha ao criar o arquivo"));
+    writeln!(out, "NAME: {}", name).unwrap();
+    writeln!(out, "TYPE: TOUR").unwrap();
+    writeln!(out, "DIMENSION: {}", tour.len()).unwrap();
+    writeln!(out, "TOUR_SECTION").unwrap();
+    for &city in tour {
+        writeln!(out, "{}", city + 1).unwrap();
+    }
+    writeln!(out, "-1").unwrap();
+    writeln!(out, "EOF").unwrap();
+}
+
+// Weights listed row by row over the cells of the given format, which for triangular
+// formats are mirrored into a symmetric matrix
+fn explicit_distances(n: usize, format: &str, weights: &Vec<Cost>) -> Vec<Vec<Cost>> {
+    let mut distances = vec![vec![0; n]; n];
+    let cells: Vec<(usize, usize)> = match format {
+        "FULL_MATRIX" => (0..n).flat_map(|i| (0..n).map(move |j| (i, j))).collect(),
+        "UPPER_ROW" => (0..n).flat_map(|i| (i+1..n).map(move |j| (i, j))).collect(),
+        "LOWER_ROW" => (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).collect(),
+        "UPPER_DIAG_ROW" => (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect(),
+        "LOWER_DIAG_ROW" => (0..n).flat_map(|i| (0..=i).map(move |j| (i, j))).collect(),
+        _ => panic!("Formato de pesos nao suportado: {}", format),
+    };
+    for (&(i, j), &weight) in cells.iter().zip(weights) {
+        distances[i][j] = weight;
+        if format != "FULL_MATRIX" {
+            distances[j][i] = weight;
+        }
+    }
+    // ATSP files often store a large weight (9999, 100000000) on the diagonal
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[i] = 0;
+    }
+    distances
+}
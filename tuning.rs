@@ -0,0 +1,225 @@
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use crate::analysis;
+use crate::solver::Params;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Range {
+    Integer(i64, i64),
+    Real(f64, f64),
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub range: Range,
+}
+
+impl Parameter {
+    pub fn integer(name: &str, min: i64, max: i64) -> Self {
+        Self { name: name.to_string(), range: Range::Integer(min, max) }
+    }
+
+    pub fn real(name: &str, min: f64, max: f64) -> Self {
+        Self { name: name.to_string(), range: Range::Real(min, max) }
+    }
+
+    fn bounds(&self) -> (f64, f64) {
+        match self.range {
+            Range::Integer(min, max) => (min as f64, max as f64),
+            Range::Real(min, max) => (min, max),
+        }
+    }
+
+    fn clamp(&self, value: f64) -> f64 {
+        let (min, max) = self.bounds();
+        let value = value.max(min).min(max);
+        match self.range {
+            Range::Integer(_, _) => value.round(),
+            Range::Real(_, _) => value,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Configuration {
+    pub id: usize,
+    pub names: Vec<String>,
+    pub values: Vec<f64>,
+    pub mean_rank: f64,
+}
+
+impl Configuration {
+    // The values as solver parameters
+    pub fn params(&self) -> Params {
+        let mut params = Params::default();
+        for (name, value) in self.names.iter().zip(&self.values) {
+            params.set(name, &value.to_string());
+        }
+        params
+    }
+}
+
+fn rank_sums(blocks: &Vec<Vec<f64>>) -> Vec<f64> {
+    let mut sums = vec![0.0; blocks[0].len()];
+    for block in blocks {
+        for (c, rank) in analysis::average_ranks(block).iter().enumerate() {
+            sums[c] += rank;
+        }
+    }
+    sums
+}
+
+pub struct Race {
+    pub parameters: Vec<Parameter>,
+    pub max_experiments: usize,
+    pub n_elites: usize,
+    pub first_test: usize,
+    pub alpha: f64,
+    pub seed: u64,
+    // Progress of the race on stderr
+    pub verbose: bool,
+}
+
+impl Race {
+    pub fn new(parameters: Vec<Parameter>, max_experiments: usize) -> Self {
+        Self {
+            parameters,
+            max_experiments,
+            n_elites: 5,
+            first_test: 5,
+            alpha: 0.05,
+            seed: 0,
+            verbose: false,
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.parameters.iter().map(|p| p.name.clone()).collect()
+    }
+
+    fn sample_uniform(&self, id: usize, rng: &mut StdRng) -> Configuration {
+        let values = self.parameters.iter().map(|p| {
+            let (min, max) = p.bounds();
+            p.clamp(rng.gen_range(min..=max))
+        }).collect();
+        Configuration { id, names: self.names(), values, mean_rank: 0.0 }
+    }
+
+    // New configuration around an elite, chosen with probability decreasing with its rank
+    fn sample_around(&self, id: usize, elites: &[Configuration], std_factor: f64, rng: &mut StdRng) -> Configuration {
+        let n = elites.len();
+        let weights: Vec<f64> = (0..n).map(|r| (n - r) as f64).collect();
+        let total: f64 = weights.iter().sum();
+        let mut choice = rng.gen_range(0.0..total);
+        let mut parent = &elites[0];
+        for (r, w) in weights.iter().enumerate() {
+            if choice < *w {
+                parent = &elites[r];
+                break;
+            }
+            choice -= w;
+        }
+
+        let values = self.parameters.iter().enumerate().map(|(i, p)| {
+            let (min, max) = p.bounds();
+            let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+            let u2: f64 = rng.gen_range(0.0..1.0);
+            let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+            p.clamp(parent.values[i] + normal * std_factor * (max - min))
+        }).collect();
+        Configuration { id, names: self.names(), values, mean_rank: 0.0 }
+    }
+
+    // `evaluate(config, instance, seed)` runs the solver and returns the cost (lower is better)
+    pub fn run<F>(&self, n_instances: usize, evaluate: F) -> Vec<Configuration>
+    where F: Fn(&Configuration, usize, u64) -> f64 {
+        if n_instances == 0 {
+            panic!("Nenhuma instancia para a corrida");
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let n_iterations = 2 + (self.parameters.len() as f64).log2().ceil() as usize;
+        let mut used = 0;
+        let mut next_id = 0;
+        let mut elites: Vec<Configuration> = vec![];
+        let mut std_factor = 0.5;
+
+        for iteration in 0..n_iterations {
+            let budget = self.max_experiments.saturating_sub(used) / (n_iterations - iteration);
+            let n_configs = (budget / (self.first_test + iteration.min(5))).max(elites.len() + 1);
+            let mut alive: Vec<Configuration> = elites.clone();
+            while alive.len() < n_configs {
+                let config = if elites.is_empty() {
+                    self.sample_uniform(next_id, &mut rng)
+                } else {
+                    self.sample_around(next_id, &elites, std_factor, &mut rng)
+                };
+                next_id += 1;
+                alive.push(config);
+            }
+            if self.verbose {
+                eprintln!("Iteracao {}: {} configuracoes, orcamento {}", iteration, alive.len(), budget);
+            }
+
+            let mut order: Vec<usize> = (0..n_instances).collect();
+            order.shuffle(&mut rng);
+            let mut results: Vec<Vec<f64>> = vec![vec![]; alive.len()];
+            let mut spent = 0;
+            let mut step = 0;
+            // Every configuration, elites included, is evaluated on at least `first_test`
+            // instances, even when the budget is short
+            while step < self.first_test || (spent + alive.len() <= budget && alive.len() > self.n_elites) {
+                let instance = order[step % n_instances];
+                let seed = self.seed ^ ((iteration as u64) << 32) ^ step as u64;
+                for (c, config) in alive.iter().enumerate() {
+                    results[c].push(evaluate(config, instance, seed));
+                }
+                spent += alive.len();
+                step += 1;
+
+                if step >= self.first_test {
+                    let blocks: Vec<Vec<f64>> = (0..step)
+                        .map(|b| results.iter().map(|r| r[b]).collect())
+                        .collect();
+                    let mut survivors = analysis::conover_survivors(&blocks, self.alpha);
+                    if survivors.len() < self.n_elites {
+                        let rank_sums = rank_sums(&blocks);
+                        let mut best: Vec<usize> = (0..alive.len()).collect();
+                        best.sort_by(|&a, &b| rank_sums[a].total_cmp(&rank_sums[b]));
+                        best.truncate(self.n_elites);
+                        best.sort();
+                        survivors = best;
+                    }
+                    if survivors.len() < alive.len() {
+                        alive = survivors.iter().map(|&s| alive[s].clone()).collect();
+                        results = survivors.iter().map(|&s| results[s].clone()).collect();
+                        if self.verbose {
+                            eprintln!("  bloco {}: {} configuracoes restantes", step, alive.len());
+                        }
+                    }
+                }
+            }
+            used += spent;
+
+            if step > 0 {
+                let blocks: Vec<Vec<f64>> = (0..step)
+                    .map(|b| results.iter().map(|r| r[b]).collect())
+                    .collect();
+                let rank_sums = rank_sums(&blocks);
+                for (c, config) in alive.iter_mut().enumerate() {
+                    config.mean_rank = rank_sums[c] / step as f64;
+                }
+            }
+            alive.sort_by(|a, b| a.mean_rank.total_cmp(&b.mean_rank));
+            alive.truncate(self.n_elites);
+            elites = alive;
+            std_factor *= (1.0 / n_configs as f64).powf(1.0 / self.parameters.len() as f64);
+            if used >= self.max_experiments {
+                break;
+            }
+        }
+        elites
+    }
+}